@@ -21,7 +21,7 @@ macro bench_rayon($name: ident, $t: expr, $n: expr, $m: expr) {
         // > Executes `op` within the threadpool. Any attempts to use `join`, `scope`,
         // > or parallel iterators will then operate within that threadpool.
         thread_pool.install(|| {
-            bencher.iter(|| AdjLists::gen_directed($n, $m, None))
+            bencher.iter(|| AdjLists::<usize>::gen_directed($n, $m, None))
         });
     }
 }
@@ -29,7 +29,7 @@ macro bench_rayon($name: ident, $t: expr, $n: expr, $m: expr) {
 macro bench_custom($name: ident, $t: expr, $n: expr, $m: expr) {
     #[bench]
     fn $name(bencher: &mut Bencher) {
-        bencher.iter(|| AdjLists::gen_directed_on_threads($n, $m, $t, None))
+        bencher.iter(|| AdjLists::<usize>::gen_directed_on_threads($n, $m, $t, None))
     }
 }
 
@@ -0,0 +1,129 @@
+#![feature(decl_macro)]
+#![feature(test)]
+
+extern crate parallel_dfs;
+extern crate rayon;
+extern crate test;
+
+use parallel_dfs::dfs;
+use parallel_dfs::graph::{AdjLists, Edge};
+use rayon::ThreadPoolBuilder;
+use test::Bencher;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Allocator which keeps track of memory usage, so that the benchmarks
+/// can show how much memory the algorithms need and not only how fast
+/// they are.
+struct Counting;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+static TOTAL: AtomicUsize = AtomicUsize::new(0);
+
+impl Counting {
+    fn add(size: usize) {
+        let current = CURRENT.fetch_add(size, Ordering::SeqCst) + size;
+        TOTAL.fetch_add(size, Ordering::SeqCst);
+
+        let mut peak = PEAK.load(Ordering::SeqCst);
+        while current > peak {
+            match PEAK.compare_exchange(peak, current, Ordering::SeqCst, Ordering::SeqCst) {
+                Ok(_) => break,
+                Err(found) => peak = found,
+            }
+        }
+    }
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            Counting::add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            Counting::add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// Upper bound of the memory `dfs::par` may use per vertex of a chain.
+///
+/// Before the backtrack phase used the `owner` array, every node on the
+/// backtrack stack had its own `vec![false; n_verts]`, i.e. `n_verts` bytes
+/// per vertex of a chain, which is far more than this already for 10k
+/// vertices.
+const PEAK_BYTES_PER_VERTEX: usize = 256;
+
+/// Graph with edges *(0, 1), (1, 2), ..., (n - 2, n - 1)*. The DFS tree is
+/// as deep as possible.
+fn chain(n_verts: usize) -> AdjLists {
+    let mut graph = AdjLists::new(n_verts);
+
+    for v in 1..n_verts {
+        graph.add_edge(Edge::new(v - 1, v));
+    }
+
+    graph
+}
+
+macro bench_chain($name: ident, $t: expr, $n: expr) {
+    #[bench]
+    fn $name(bencher: &mut Bencher) {
+        let graph = chain($n);
+
+        let thread_pool = ThreadPoolBuilder::new()
+            .num_threads($t)
+            .build()
+            .unwrap();
+
+        thread_pool.install(|| {
+            // Measure the memory of a single run first
+            let before = CURRENT.load(Ordering::SeqCst);
+            PEAK.store(before, Ordering::SeqCst);
+            TOTAL.store(0, Ordering::SeqCst);
+
+            dfs::par(&graph);
+
+            let peak = PEAK.load(Ordering::SeqCst) - before;
+
+            eprintln!(
+                "{}: peak {} KiB ({} bytes per vertex), allocated {} KiB in total",
+                stringify!($name),
+                peak / 1024,
+                peak / $n,
+                TOTAL.load(Ordering::SeqCst) / 1024,
+            );
+
+            assert!(
+                peak <= PEAK_BYTES_PER_VERTEX * $n,
+                "peak memory isn't linear in the number of vertices"
+            );
+
+            bencher.iter(|| dfs::par(&graph));
+        });
+    }
+}
+
+bench_chain!(par_chain_t01_n10k, 1, 10_000);
+bench_chain!(par_chain_t04_n10k, 4, 10_000);
+bench_chain!(par_chain_t08_n10k, 8, 10_000);
+bench_chain!(par_chain_t01_n100k, 1, 100_000);
+bench_chain!(par_chain_t04_n100k, 4, 100_000);
+bench_chain!(par_chain_t08_n100k, 8, 100_000);
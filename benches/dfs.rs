@@ -1,77 +1,111 @@
-#![feature(decl_macro)]
+//! Benchmarks of every algorithm on every graph representation
+//!
+//! The benchmarks are made from `Algorithm::ALL` and `Representation::ALL`,
+//! so a new algorithm or representation is benchmarked without changes
+//! here. The harness is our own, but the timing is the one of `#[bench]`.
+//!
+//! ```text
+//! cargo bench --bench dfs -- steal_mat
+//! ```
+//!
+//! runs only the benchmarks whose name contains `steal_mat`.
+
 #![feature(test)]
 
 extern crate parallel_dfs;
-extern crate rand;
 extern crate rayon;
 extern crate test;
 
-use parallel_dfs::graph::{AdjLists, AdjMatrix};
-use parallel_dfs::dfs;
-use rand::prelude::*;
-use rand::distributions::Standard;
-use rand::prng::XorShiftRng;
+use parallel_dfs::dfs::{Algorithm, DfsConfig};
+use parallel_dfs::graph::{AdjLists, Edge, Graph, Representation};
 use rayon::ThreadPoolBuilder;
-use test::Bencher;
-
-const SEED: [u8; 16] = [130, 241, 105, 144, 39, 87, 188, 11, 85, 171, 153, 10, 140, 0, 21, 127];
-
-macro bench_par($name: ident, $Graph: ty, $t: expr, $n: expr, $m: expr) {
-    #[bench]
-    fn $name(bencher: &mut Bencher) {
-        let mut rng = XorShiftRng::from_seed(SEED);
-        let graph = <$Graph>::gen_directed($n, $m, rng.sample_iter(&Standard));
-
-        let thread_pool = ThreadPoolBuilder::new()
-            .num_threads($t)
-            .build()
-            .unwrap();
-
-        // from rayon docs:
-        // > Executes `op` within the threadpool. Any attempts to use `join`, `scope`,
-        // > or parallel iterators will then operate within that threadpool.
-        thread_pool.install(|| {
-            bencher.iter(|| dfs::par(&graph));
-        });
+
+use std::env;
+
+const SEED: u64 = 0x0bbc_5727_9069_f182;
+
+const THREADS: [usize; 13] = [1, 2, 4, 6, 8, 10, 12, 14, 16, 20, 24, 28, 32];
+
+/// Benchmark `algorithm` on `graph` with `threads` threads and `config`,
+/// unless `name` doesn't match the filter.
+fn bench(
+    filter: &Option<String>,
+    name: &str,
+    graph: &dyn Graph,
+    algorithm: Algorithm,
+    threads: usize,
+    config: &DfsConfig,
+) {
+    match *filter {
+        Some(ref filter) if !name.contains(&filter[..]) => return,
+        _ => {},
     }
+
+    let pool = ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+    let config = DfsConfig {
+        pool: Some(&pool),
+        ..config.clone()
+    };
+
+    let summary = test::bench::iter(&mut || algorithm.run_with(graph, &config));
+
+    println!(
+        "test {:<32} ... bench: {:>14.0} ns/iter (+/- {:.0})",
+        name,
+        summary.median,
+        summary.max - summary.min,
+    );
 }
 
-macro bench_seq($name: ident, $Graph: ty, $n: expr, $m: expr) {
-    #[bench]
-    fn $name(bencher: &mut Bencher) {
-        let mut rng = XorShiftRng::from_seed(SEED);
-        let graph = <$Graph>::gen_directed($n, $m, rng.sample_iter(&Standard));
+/// Graph made of `n_verts / 2` components with a single edge each.
+fn pairs(n_verts: usize) -> AdjLists {
+    let mut graph = AdjLists::new(n_verts);
 
-        bencher.iter(|| dfs::seq(&graph));
+    for v in (0..n_verts - 1).step_by(2) {
+        graph.add_edge(Edge::new(v, v + 1));
     }
+
+    graph
 }
 
-bench_seq!(seq_list_n4k_m400k, AdjLists, 4_000, 400_000);
-bench_par!(par_list_t01_n4k_m400k, AdjLists, 1, 4_000, 400_000);
-bench_par!(par_list_t02_n4k_m400k, AdjLists, 2, 4_000, 400_000);
-bench_par!(par_list_t04_n4k_m400k, AdjLists, 4, 4_000, 400_000);
-bench_par!(par_list_t06_n4k_m400k, AdjLists, 6, 4_000, 400_000);
-bench_par!(par_list_t08_n4k_m400k, AdjLists, 8, 4_000, 400_000);
-bench_par!(par_list_t10_n4k_m400k, AdjLists, 10, 4_000, 400_000);
-bench_par!(par_list_t12_n4k_m400k, AdjLists, 12, 4_000, 400_000);
-bench_par!(par_list_t14_n4k_m400k, AdjLists, 14, 4_000, 400_000);
-bench_par!(par_list_t16_n4k_m400k, AdjLists, 16, 4_000, 400_000);
-bench_par!(par_list_t20_n4k_m400k, AdjLists, 20, 4_000, 400_000);
-bench_par!(par_list_t24_n4k_m400k, AdjLists, 24, 4_000, 400_000);
-bench_par!(par_list_t28_n4k_m400k, AdjLists, 28, 4_000, 400_000);
-bench_par!(par_list_t32_n4k_m400k, AdjLists, 32, 4_000, 400_000);
-
-bench_seq!(seq_mat_n4k_m400k, AdjMatrix, 4_000, 400_000);
-bench_par!(par_mat_t01_n4k_m400k, AdjMatrix, 1, 4_000, 400_000);
-bench_par!(par_mat_t02_n4k_m400k, AdjMatrix, 2, 4_000, 400_000);
-bench_par!(par_mat_t04_n4k_m400k, AdjMatrix, 4, 4_000, 400_000);
-bench_par!(par_mat_t06_n4k_m400k, AdjMatrix, 6, 4_000, 400_000);
-bench_par!(par_mat_t08_n4k_m400k, AdjMatrix, 8, 4_000, 400_000);
-bench_par!(par_mat_t10_n4k_m400k, AdjMatrix, 10, 4_000, 400_000);
-bench_par!(par_mat_t12_n4k_m400k, AdjMatrix, 12, 4_000, 400_000);
-bench_par!(par_mat_t14_n4k_m400k, AdjMatrix, 14, 4_000, 400_000);
-bench_par!(par_mat_t16_n4k_m400k, AdjMatrix, 16, 4_000, 400_000);
-bench_par!(par_mat_t20_n4k_m400k, AdjMatrix, 20, 4_000, 400_000);
-bench_par!(par_mat_t24_n4k_m400k, AdjMatrix, 24, 4_000, 400_000);
-bench_par!(par_mat_t28_n4k_m400k, AdjMatrix, 28, 4_000, 400_000);
-bench_par!(par_mat_t32_n4k_m400k, AdjMatrix, 32, 4_000, 400_000);
+fn main() {
+    // `cargo bench` passes `--bench`, anything else is a filter
+    let filter = env::args().skip(1).find(|arg| !arg.starts_with('-'));
+    let default = DfsConfig::default();
+
+    for &representation in &Representation::ALL {
+        let graph = representation.generate(4_000, 400_000, false, Some(SEED));
+
+        for &algorithm in &Algorithm::ALL {
+            // The sequential DFS doesn't use the pool
+            let threads = match algorithm {
+                Algorithm::Seq => &THREADS[..1],
+                _ => &THREADS[..],
+            };
+
+            for &t in threads {
+                let name = format!("{}_{}_t{:02}_n4k_m400k", algorithm, representation, t);
+                bench(&filter, &name, &*graph, algorithm, t, &default);
+            }
+        }
+
+        // `dfs::par` as it was before the sequential cutoff, with a separate
+        // task for every backtrack node
+        let no_cutoff = DfsConfig {
+            sequential_cutoff: 0,
+            ..DfsConfig::default()
+        };
+
+        for &t in &[1, 4, 8, 16] {
+            let name = format!("par_nocut_{}_t{:02}_n4k_m400k", representation, t);
+            bench(&filter, &name, &*graph, Algorithm::Par, t, &no_cutoff);
+        }
+    }
+
+    let graph = pairs(100_000);
+
+    for &t in &[1, 4, 8] {
+        let name = format!("cheat_pairs_t{:02}_n100k", t);
+        bench(&filter, &name, &graph, Algorithm::Cheat, t, &default);
+    }
+}
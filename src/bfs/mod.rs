@@ -0,0 +1,135 @@
+//! Parallel level-synchronous BFS
+//!
+//! Used as a baseline to compare the parallel DFS algorithms against.
+//! The result has the same shape as the one from the functions in `dfs`.
+
+use rayon::prelude::*;
+
+use crate::graph::{AdjMatrix, Edge, GraphRef, Tree, VertexId};
+use crate::utils::BitVec;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Frontier size (as a fraction of all vertices) above which
+/// `direction_optimizing` switches to bottom-up steps.
+const BOTTOM_UP_DIVISOR: usize = 20;
+
+/// Perform a parallel top-down BFS traversal of the graph and build a forest
+/// showing how it was traversed.
+///
+/// Roots are chosen in the same order as in `dfs::seq`. Each tree is built
+/// level by level, with all vertices on the current level expanded in
+/// parallel.
+pub fn top_down<'a, G: GraphRef<'a> + Copy + Sync>(graph: G) -> Vec<Tree<G::Vertex>> {
+    let used = (0..graph.n_verts())
+        .map(|_| AtomicBool::new(false))
+        .collect::<Vec<_>>();
+
+    graph
+        .vertices()
+        .filter_map(|root| {
+            if !take(&used[root.index()]) {
+                return None;
+            }
+
+            let mut tree = Tree::new(root);
+            let mut frontier = vec![root];
+
+            while !frontier.is_empty() {
+                let level = top_down_step(graph, &used, &frontier);
+
+                frontier = level.iter().map(|edge| edge.to).collect();
+                tree.edges.extend(level);
+            }
+
+            Some(tree)
+        })
+        .collect()
+}
+
+/// Perform a parallel direction-optimizing BFS traversal of the graph.
+///
+/// Same as `top_down`, but when the frontier gets large it is cheaper to go
+/// over the unvisited vertices and look for a parent in the frontier, using
+/// the constant time edge lookup of the matrix.
+pub fn direction_optimizing(graph: &AdjMatrix) -> Vec<Tree> {
+    let n_verts = graph.n_verts();
+    let used = (0..n_verts)
+        .map(|_| AtomicBool::new(false))
+        .collect::<Vec<_>>();
+
+    let in_frontier = BitVec::new(n_verts);
+
+    graph
+        .vertices()
+        .filter_map(|root| {
+            if !take(&used[root]) {
+                return None;
+            }
+
+            let mut tree = Tree::new(root);
+            let mut frontier = vec![root];
+
+            while !frontier.is_empty() {
+                let level = if frontier.len() > n_verts / BOTTOM_UP_DIVISOR {
+                    frontier.par_iter().for_each(|&v| in_frontier.set(v, true));
+                    let level = bottom_up_step(graph, &used, &in_frontier);
+                    frontier.par_iter().for_each(|&v| in_frontier.set(v, false));
+
+                    level
+                } else {
+                    top_down_step(graph, &used, &frontier)
+                };
+
+                frontier = level.iter().map(|edge| edge.to).collect();
+                tree.edges.extend(level);
+            }
+
+            Some(tree)
+        })
+        .collect()
+}
+
+/// Expand every vertex in the frontier and return the edges to the
+/// vertices of the next level.
+fn top_down_step<'a, G: GraphRef<'a> + Copy + Sync>(
+    graph: G,
+    used: &[AtomicBool],
+    frontier: &[G::Vertex],
+) -> Vec<Edge<G::Vertex>> {
+    frontier
+        .par_iter()
+        .flat_map(|&u| {
+            graph
+                .neighbours(u)
+                .filter(|&v| take(&used[v.index()]))
+                .map(|v| Edge::new(u, v))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Find a parent in the frontier for every unvisited vertex and return
+/// the edges to the vertices of the next level.
+fn bottom_up_step(graph: &AdjMatrix, used: &[AtomicBool], in_frontier: &BitVec) -> Vec<Edge> {
+    graph
+        .vertices_par()
+        .filter(|&v| !used[v].load(Ordering::SeqCst))
+        .filter_map(|v| {
+            let parent = graph
+                .vertices()
+                .find(|&u| in_frontier.get(u).unwrap() && graph.has_edge(u, v))?;
+
+            // `v` is checked only by this thread, no need to `take` it
+            used[v].store(true, Ordering::SeqCst);
+            Some(Edge::new(parent, v))
+        })
+        .collect()
+}
+
+fn take(used: &AtomicBool) -> bool {
+    !used.swap(true, Ordering::SeqCst)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,76 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::graph::{tree, AdjLists, AdjMatrix, GraphRef, Tree};
+use super::*;
+
+/// Depth of every vertex in the forest.
+fn depths(forest: &[Tree]) -> HashMap<usize, usize> {
+    let mut depth = HashMap::new();
+
+    for tree in forest {
+        depth.insert(tree.root, 0);
+
+        // Edges are added level by level, so the parent of every
+        // edge is already known.
+        for edge in &tree.edges {
+            let d = depth[&edge.from] + 1;
+            depth.insert(edge.to, d);
+        }
+    }
+
+    depth
+}
+
+/// Simple sequential BFS to compare against.
+fn answer<'a, G: GraphRef<'a, Vertex = usize> + Copy>(graph: G) -> HashMap<usize, usize> {
+    let mut depth = HashMap::new();
+    let mut queue = VecDeque::new();
+
+    for root in graph.vertices() {
+        if depth.contains_key(&root) {
+            continue;
+        }
+
+        depth.insert(root, 0);
+        queue.push_back(root);
+
+        while let Some(u) = queue.pop_front() {
+            for v in graph.neighbours(u) {
+                if !depth.contains_key(&v) {
+                    let d = depth[&u] + 1;
+                    depth.insert(v, d);
+                    queue.push_back(v);
+                }
+            }
+        }
+    }
+
+    depth
+}
+
+#[test]
+fn visits_all() {
+    let graph: AdjLists = AdjLists::gen_directed(100, 1000, None);
+    let forest = top_down(&graph);
+
+    assert!(tree::is_spanning(graph.vertices().count(), &forest));
+}
+
+// Parents may differ between runs, but the level of each vertex may not.
+#[test]
+fn top_down_levels() {
+    let graph: AdjLists = AdjLists::gen_directed(300, 1000, None);
+    assert_eq!(depths(&top_down(&graph)), answer(&graph));
+
+    let graph = AdjMatrix::gen_directed(300, 1000, None);
+    assert_eq!(depths(&top_down(&graph)), answer(&graph));
+}
+
+#[test]
+fn direction_optimizing_levels() {
+    let graph = AdjMatrix::gen_directed(300, 1000, None);
+    assert_eq!(depths(&direction_optimizing(&graph)), answer(&graph));
+
+    let graph = AdjMatrix::gen_undirected(300, 3000, None);
+    assert_eq!(depths(&direction_optimizing(&graph)), answer(&graph));
+}
@@ -0,0 +1,102 @@
+//! Registry of the traversal algorithms
+
+use crate::bfs;
+use crate::dfs::config::DfsConfig;
+use crate::dfs::{cheat, par, seq, steal};
+use crate::graph::{Graph, GraphRef, Tree, VertexId};
+
+use std::fmt;
+use std::str::FromStr;
+
+/// All traversals which build a forest from a graph.
+///
+/// Used to choose an algorithm at runtime, e.g. from the command line, and to
+/// go over all of them in tests and benchmarks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    Seq,
+    Par,
+    Cheat,
+    Steal,
+    Bfs,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 5] = [
+        Algorithm::Seq,
+        Algorithm::Par,
+        Algorithm::Cheat,
+        Algorithm::Steal,
+        Algorithm::Bfs,
+    ];
+
+    /// Short name used on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Seq => "seq",
+            Algorithm::Par => "par",
+            Algorithm::Cheat => "cheat",
+            Algorithm::Steal => "steal",
+            Algorithm::Bfs => "bfs",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Algorithm::Seq => "sequential DFS",
+            Algorithm::Par => "parallel DFS with a descend and a backtrack phase",
+            Algorithm::Cheat => "parallel DFS growing a tree from every vertex",
+            Algorithm::Steal => "parallel DFS with work stealing",
+            Algorithm::Bfs => "parallel BFS, as a baseline",
+        }
+    }
+
+    /// Run the algorithm on `graph`.
+    pub fn run<V: VertexId>(self, graph: &dyn Graph<V>) -> Vec<Tree<V>> {
+        graph.traverse(self, &DfsConfig::default())
+    }
+
+    /// Same as `run`, but with custom options.
+    pub fn run_with<V: VertexId>(self, graph: &dyn Graph<V>, config: &DfsConfig) -> Vec<Tree<V>> {
+        graph.traverse(self, config)
+    }
+
+    /// Run the algorithm on any `GraphRef`.
+    ///
+    /// This is what `Graph::traverse` usually calls, after picking anything
+    /// specific to the representation.
+    pub fn run_ref<'a, G: GraphRef<'a> + Copy + Send + Sync>(
+        self,
+        graph: G,
+        config: &DfsConfig,
+    ) -> Vec<Tree<G::Vertex>> {
+        match self {
+            Algorithm::Seq => seq::run_with(graph, config),
+            Algorithm::Par => par::run_with(graph, config),
+            Algorithm::Cheat => cheat::run_with(graph, config),
+            Algorithm::Steal => steal::run_with(graph, config),
+            Algorithm::Bfs => config.install(|| bfs::top_down(graph)),
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Algorithm::ALL
+            .iter()
+            .cloned()
+            .find(|algorithm| algorithm.name() == s)
+            .ok_or_else(|| format!("unknown algorithm {:?}", s))
+    }
+}
+
+#[cfg(test)]
+mod tests;
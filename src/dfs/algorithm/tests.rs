@@ -0,0 +1,64 @@
+use crate::graph::{tree, Representation};
+use crate::progress::{Phase, Progress};
+use super::*;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Sink which counts visited vertices.
+#[derive(Default)]
+struct Counter(AtomicUsize);
+
+impl Progress for Counter {
+    fn phase(&self, _: Phase) {}
+
+    fn edges_generated(&self, _: usize) {}
+
+    fn vertices_visited(&self, n: usize) {
+        self.0.fetch_add(n, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn names() {
+    for &algorithm in &Algorithm::ALL {
+        assert_eq!(algorithm.name().parse::<Algorithm>(), Ok(algorithm));
+    }
+
+    assert!("dfs".parse::<Algorithm>().is_err());
+}
+
+#[test]
+fn visits_all() {
+    for &representation in &Representation::ALL {
+        let graph = representation.generate(100, 1000, false, None);
+
+        for &algorithm in &Algorithm::ALL {
+            let forest = algorithm.run(&*graph);
+
+            assert!(tree::is_spanning(100, &forest), "{} on {}", algorithm, representation);
+        }
+    }
+}
+
+// Traversals running at the same time report to their own sinks.
+#[test]
+fn progress_sinks() {
+    let graph = Representation::Lists.generate(3000, 10000, false, None);
+
+    // BFS doesn't report progress
+    for &algorithm in Algorithm::ALL.iter().filter(|&&a| a != Algorithm::Bfs) {
+        let (first, second) = (Counter::default(), Counter::default());
+        let config = |sink| DfsConfig {
+            progress: Some(sink),
+            ..DfsConfig::default()
+        };
+
+        rayon::join(
+            || algorithm.run_with(&*graph, &config(&first)),
+            || algorithm.run_with(&*graph, &config(&second)),
+        );
+
+        assert_eq!(first.0.load(Ordering::SeqCst), 3000, "{}", algorithm);
+        assert_eq!(second.0.load(Ordering::SeqCst), 3000, "{}", algorithm);
+    }
+}
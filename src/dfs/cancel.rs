@@ -0,0 +1,148 @@
+//! Cancellation of long running traversals
+
+use crate::graph::{Tree, VertexId};
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How many vertices a worker visits between checks of the token.
+const CHECK_INTERVAL: usize = 1024;
+
+/// Handle used to stop a traversal before it finishes.
+///
+/// The token can be cloned and cancelled from any thread. It can also have
+/// a deadline after which it counts as cancelled.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancellationToken {
+    /// Create new token which is cancelled only by calling `cancel`.
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    /// Create new token which is cancelled at `deadline`.
+    pub fn with_deadline(deadline: Instant) -> Self {
+        CancellationToken {
+            cancelled: Arc::new(AtomicBool::new(false)),
+            deadline: Some(deadline),
+        }
+    }
+
+    /// Create new token which is cancelled after `timeout` from now.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self::with_deadline(Instant::now() + timeout)
+    }
+
+    /// Cancel the token and all its clones.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        if self.cancelled.load(Ordering::SeqCst) {
+            return true;
+        }
+
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => {
+                self.cancel();
+                true
+            },
+            _ => false,
+        }
+    }
+}
+
+/// Result of a traversal which may have been cancelled.
+#[derive(Debug, Clone)]
+pub enum Traversal<V: VertexId = usize> {
+    /// The traversal finished and the forest covers the whole graph.
+    Complete(Vec<Tree<V>>),
+    /// The traversal was cancelled. `forest` contains the trees built until
+    /// then and `unvisited` all vertices which aren't part of them.
+    Partial {
+        forest: Vec<Tree<V>>,
+        unvisited: Vec<V>,
+    },
+}
+
+impl<V: VertexId> Traversal<V> {
+    /// Build the result from the forest a traversal ended up with.
+    pub(super) fn new(forest: Vec<Tree<V>>, n_verts: usize, token: &CancellationToken) -> Self {
+        // If nobody saw the token cancelled the traversal must have finished.
+        if !token.is_cancelled() {
+            return Traversal::Complete(forest);
+        }
+
+        let mut visited = vec![false; n_verts];
+
+        for tree in &forest {
+            visited[tree.root.index()] = true;
+
+            for edge in &tree.edges {
+                visited[edge.to.index()] = true;
+            }
+        }
+
+        let unvisited = (0..n_verts)
+            .filter(|&v| !visited[v])
+            .map(V::from_index)
+            .collect::<Vec<_>>();
+
+        match unvisited.is_empty() {
+            true => Traversal::Complete(forest),
+            false => Traversal::Partial { forest, unvisited },
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        match *self {
+            Traversal::Complete(_) => true,
+            Traversal::Partial { .. } => false,
+        }
+    }
+
+    /// The forest built by the traversal, whether it completed or not.
+    pub fn into_forest(self) -> Vec<Tree<V>> {
+        match self {
+            Traversal::Complete(forest) => forest,
+            Traversal::Partial { forest, .. } => forest,
+        }
+    }
+}
+
+/// Per worker wrapper which checks the token only every `CHECK_INTERVAL`
+/// vertices, so that hot loops don't have to read the clock each time.
+pub(super) struct Checker<'t> {
+    token: &'t CancellationToken,
+    count: usize,
+    cancelled: bool,
+}
+
+impl<'t> Checker<'t> {
+    pub fn new(token: &'t CancellationToken) -> Self {
+        Checker {
+            token,
+            count: 0,
+            cancelled: false,
+        }
+    }
+
+    /// Count one more visited vertex and return whether the traversal
+    /// should stop. The token is checked on the first call, so that a worker
+    /// doesn't start on a cancelled traversal, and then every
+    /// `CHECK_INTERVAL` calls.
+    pub fn is_cancelled(&mut self) -> bool {
+        if self.count % CHECK_INTERVAL == 0 {
+            self.cancelled = self.token.is_cancelled();
+        }
+
+        self.count += 1;
+        self.cancelled
+    }
+}
@@ -2,17 +2,56 @@
 
 use rayon::prelude::*;
 
-use crate::graph::{Edge, GraphRef, Tree};
+use crate::dfs::cancel::{CancellationToken, Checker, Traversal};
+use crate::dfs::config::DfsConfig;
+use crate::graph::{Edge, GraphRef, Tree, VertexId};
+use crate::progress::{Phase, Visits};
 
 use std::sync::atomic::AtomicUsize;
 use std::usize;
 
-mod util;
-use self::util::{take_ownership, State};
+pub(super) mod util;
+use self::util::{take_ownership, Pool, State};
 
 /// Perform a sequential DFS traversal of the graph and build a forest showing
 /// how it was traversed.
-pub fn run<'a, G: GraphRef<'a> + Copy + Sync>(graph: G) -> Vec<Tree> {
+pub fn run<'a, G: GraphRef<'a> + Copy + Sync>(graph: G) -> Vec<Tree<G::Vertex>> {
+    run_inner(graph, &DfsConfig::default(), &CancellationToken::new()).into_forest()
+}
+
+/// Same as `run`, but on the thread pool from `config`.
+pub fn run_with<'a, G: GraphRef<'a> + Copy + Send + Sync>(
+    graph: G,
+    config: &DfsConfig,
+) -> Vec<Tree<G::Vertex>> {
+    run_cancellable_with(graph, config, &CancellationToken::new()).into_forest()
+}
+
+/// Same as `run`, but stops when `token` is cancelled.
+///
+/// Trees which are being built at that moment are cut short and no new
+/// trees are started.
+pub fn run_cancellable<'a, G: GraphRef<'a> + Copy + Sync>(
+    graph: G,
+    token: &CancellationToken,
+) -> Traversal<G::Vertex> {
+    run_inner(graph, &DfsConfig::default(), token)
+}
+
+/// Same as `run_cancellable`, but with custom options.
+pub fn run_cancellable_with<'a, G: GraphRef<'a> + Copy + Send + Sync>(
+    graph: G,
+    config: &DfsConfig,
+    token: &CancellationToken,
+) -> Traversal<G::Vertex> {
+    config.install(|| run_inner(graph, config, token))
+}
+
+fn run_inner<'a, G: GraphRef<'a> + Copy + Sync>(
+    graph: G,
+    config: &DfsConfig,
+    token: &CancellationToken,
+) -> Traversal<G::Vertex> {
     // Tracks is a given vertex is "owned" by a tree.
     // If a tree owns the vertex, its value is the id of the root node of the tree.
     // If the vertex isn't owned, its value is `usize::MAX`.
@@ -23,34 +62,48 @@ pub fn run<'a, G: GraphRef<'a> + Copy + Sync>(graph: G) -> Vec<Tree> {
     //
     // This is a shared state between all threads and guarantees that each vertex is
     // traversed only once.
-    let owner = (0..graph.vertices().count())
+    let sink = config.sink();
+    sink.phase(Phase::Traverse);
+
+    let owner = (0..graph.n_verts())
         .map(|_| AtomicUsize::new(usize::MAX))
         .collect::<Vec<_>>();
 
-    graph
+    // Shared pool of buffers for the state of each root, which a root
+    // takes when it starts and gives back for later roots when it's done
+    let pool = Pool::new();
+
+    let forest = graph
         .vertices_par()
-        .filter_map(|root| {
-            if !take_ownership(&owner[root], root) {
+        .map_init(|| Checker::new(token), |checker, root| {
+            if checker.is_cancelled() || !take_ownership(&owner[root.index()], root.index()) {
                 return None;
             }
 
+            let mut visits = Visits::new(sink);
             let mut stack = Vec::new();
-            let mut state = State::new(root, &owner);
+            let mut state = State::new(root.index(), &owner, &pool);
             let mut tree = Tree::new(root);
+            visits.add();
 
             for v in graph.neighbours(root).rev() {
-                if state.get(v).is_owned_unused() {
+                if state.get(v.index()).is_owned_unused() {
                     stack.push((root, v));
                 }
             }
 
             while let Some((parent, v)) = stack.pop() {
-                if state.get(v).is_owned_unused() {
-                    state.mark_used(v);
+                if checker.is_cancelled() {
+                    break;
+                }
+
+                if state.get(v.index()).is_owned_unused() {
+                    state.mark_used(v.index());
                     tree.add(Edge::new(parent, v));
+                    visits.add();
 
                     for child in graph.neighbours(v).rev() {
-                        if state.get(child).is_owned_unused() {
+                        if state.get(child.index()).is_owned_unused() {
                             stack.push((v, child));
                         }
                     }
@@ -59,7 +112,10 @@ pub fn run<'a, G: GraphRef<'a> + Copy + Sync>(graph: G) -> Vec<Tree> {
 
             Some(tree)
         })
-        .collect()
+        .filter_map(|tree| tree)
+        .collect();
+
+    Traversal::new(forest, owner.len(), token)
 }
 
 #[cfg(test)]
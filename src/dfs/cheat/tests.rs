@@ -1,11 +1,11 @@
 use rayon::ThreadPoolBuilder;
 
 use crate::dfs;
-use crate::graph::{AdjLists, GraphRef};
+use crate::graph::{AdjLists, Edge, GraphRef};
 
 #[test]
 fn visits_all() {
-    let graph = AdjLists::gen_directed(100, 1000, None);
+    let graph: AdjLists = AdjLists::gen_directed(100, 1000, None);
     let forest = dfs::cheat(&graph);
 
     let mut visited = vec![0; graph.vertices().count()];
@@ -31,7 +31,7 @@ fn matches_seq() {
         .unwrap();
 
     thread_pool.install(|| {
-        let graph = AdjLists::gen_directed(100, 1000, None);
+        let graph: AdjLists = AdjLists::gen_directed(100, 1000, None);
         let mut answer = dfs::seq(&graph);
         let mut forest = dfs::cheat(&graph);
 
@@ -48,3 +48,44 @@ fn matches_seq() {
         }
     });
 }
+
+#[test]
+fn cancelled() {
+    let graph: AdjLists = AdjLists::gen_directed(100, 1000, None);
+
+    let token = dfs::CancellationToken::new();
+    token.cancel();
+
+    match dfs::cheat_cancellable(&graph, &token) {
+        dfs::Traversal::Partial { forest, unvisited } => {
+            assert!(forest.is_empty());
+            assert_eq!(unvisited.len(), 100);
+        },
+        dfs::Traversal::Complete(_) => panic!("traversal should be cancelled"),
+    }
+
+    let token = dfs::CancellationToken::with_timeout(std::time::Duration::from_secs(60));
+    assert!(dfs::cheat_cancellable(&graph, &token).is_complete());
+}
+
+// Every root reuses the state left behind by the previous roots
+// on the same thread, which must not leak between trees.
+#[test]
+fn many_components() {
+    let mut graph: AdjLists = AdjLists::new(1000);
+
+    for v in (0..1000).step_by(2) {
+        graph.add_edge(Edge::new(v, v + 1));
+        graph.add_edge(Edge::new(v + 1, v));
+    }
+
+    let mut forest = dfs::cheat(&graph);
+    forest.sort_unstable_by_key(|tree| tree.root);
+    assert_eq!(forest.len(), 500);
+
+    for tree in &forest {
+        let v = tree.root - tree.root % 2;
+        assert_eq!(tree.edges.len(), 1);
+        assert!(tree.edges[0] == Edge::new(v, v + 1) || tree.edges[0] == Edge::new(v + 1, v));
+    }
+}
@@ -1,3 +1,6 @@
+use crossbeam::sync::SegQueue;
+
+use std::mem;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::usize;
 
@@ -25,49 +28,149 @@ impl VertState {
     }
 }
 
+/// Entry of a `Buffer`. The state is valid only if `generation` matches the
+/// generation of the buffer, otherwise the state is `Unknown`.
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    generation: u32,
+    state: VertState,
+}
+
+/// Storage for the state of every vertex, reused between roots.
+///
+/// Instead of clearing the whole buffer for every new root, the generation
+/// is increased, which invalidates all slots at once. This way the cost of
+/// a root is proportional to the size of its tree and not to the size of
+/// the graph.
+#[derive(Debug, Default)]
+struct Buffer {
+    generation: u32,
+    slots: Vec<Slot>,
+}
+
+impl Buffer {
+    /// Prepare the buffer for a new root.
+    fn reset(&mut self, n_verts: usize) {
+        let empty = Slot {
+            generation: 0,
+            state: VertState::Unknown,
+        };
+
+        if self.generation == u32::max_value() {
+            for slot in self.slots.iter_mut() {
+                *slot = empty;
+            }
+
+            self.generation = 0;
+        }
+
+        self.generation += 1;
+        self.slots.resize(n_verts, empty);
+    }
+
+    fn get(&self, v: usize) -> VertState {
+        match self.slots[v] {
+            slot if slot.generation == self.generation => slot.state,
+            _ => VertState::Unknown,
+        }
+    }
+
+    fn set(&mut self, v: usize, state: VertState) {
+        self.slots[v] = Slot {
+            generation: self.generation,
+            state,
+        };
+    }
+}
+
+/// Buffers for `State` which aren't in use at the moment.
+///
+/// There are never more buffers than roots being traversed at the same
+/// time, i.e. than the number of threads.
+#[derive(Debug)]
+pub struct Pool {
+    buffers: SegQueue<Buffer>,
+}
+
+impl Pool {
+    pub fn new() -> Self {
+        Pool {
+            buffers: SegQueue::new(),
+        }
+    }
+
+    fn take(&self) -> Buffer {
+        self.buffers.try_pop().unwrap_or_default()
+    }
+
+    fn put(&self, buffer: Buffer) {
+        self.buffers.push(buffer);
+    }
+}
+
 /// What a particilar root knows about the rest of the graph.
 ///
 /// This serves both as a local cache of the values in the shared
 /// `owner` array and as a `used` array.
 pub struct State<'a> {
     root: usize,
-    state: Vec<VertState>,
+    buffer: Buffer,
     owner: &'a [AtomicUsize],
+    pool: &'a Pool,
 }
 
 impl<'a> State<'a> {
-    pub fn new(root: usize, owner: &'a [AtomicUsize]) -> Self {
-        let mut state = vec![VertState::Unknown; owner.len()];
-        state[root] = VertState::OwnedUsed;
+    pub fn new(root: usize, owner: &'a [AtomicUsize], pool: &'a Pool) -> Self {
+        let mut buffer = pool.take();
+        buffer.reset(owner.len());
+        buffer.set(root, VertState::OwnedUsed);
 
         State {
             root,
-            state,
+            buffer,
             owner,
+            pool,
         }
     }
 
     pub fn get(&mut self, v: usize) -> VertState {
-        if let VertState::Unknown = self.state[v] {
+        if let VertState::Unknown = self.buffer.get(v) {
             if take_ownership(&self.owner[v], self.root) {
-                self.state[v] = VertState::OwnedUnused;
+                self.buffer.set(v, VertState::OwnedUnused);
             } else {
-                self.state[v] = VertState::NotOwned;
+                self.buffer.set(v, VertState::NotOwned);
             }
         }
 
-        self.state[v]
+        self.buffer.get(v)
+    }
+
+    /// Whether `v` can still be visited by this tree.
+    ///
+    /// Unlike `get` this doesn't try to take ownership of `v`.
+    pub fn is_unvisited(&self, v: usize) -> bool {
+        match self.buffer.get(v) {
+            VertState::Unknown => self.owner[v].load(Ordering::SeqCst) == usize::MAX,
+            VertState::OwnedUnused => true,
+            _ => false,
+        }
     }
 
     pub fn mark_used(&mut self, v: usize) {
-        match self.state[v] {
-            VertState::OwnedUnused => self.state[v] = VertState::OwnedUsed,
+        match self.buffer.get(v) {
+            VertState::OwnedUnused => self.buffer.set(v, VertState::OwnedUsed),
             VertState::OwnedUsed => (),
             _ => panic!("vertex must be owned")
         }
     }
 }
 
+impl<'a> Drop for State<'a> {
+    fn drop(&mut self) {
+        self.pool.put(mem::replace(&mut self.buffer, Buffer::default()));
+    }
+}
+
 pub fn take_ownership(owner: &AtomicUsize, root: usize) -> bool {
     let current = owner.compare_and_swap(usize::MAX, root, Ordering::SeqCst);
     current == usize::MAX
@@ -0,0 +1,81 @@
+//! Options for the parallel traversals
+
+use rayon::ThreadPool;
+
+use crate::progress::{Progress, Sink};
+
+use std::fmt;
+
+/// Options for the parallel traversals.
+///
+/// ```ignore
+/// let pool = ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+///
+/// let forest = dfs::par_with(&graph, &DfsConfig {
+///     pool: Some(&pool),
+///     ..DfsConfig::default()
+/// });
+/// ```
+#[derive(Clone)]
+pub struct DfsConfig<'p> {
+    /// Thread pool to run the traversal on. With `None` the traversal runs on
+    /// the current pool, i.e. the global one unless called from inside
+    /// `ThreadPool::install`.
+    pub pool: Option<&'p ThreadPool>,
+
+    /// Backtrack nodes of `dfs::par` are put together in a single task,
+    /// processed sequentially, until the group has more than this many
+    /// neighbours which it may still take. Heavier nodes get a task of their
+    /// own.
+    ///
+    /// With `0` every node which has any work gets its own task.
+    pub sequential_cutoff: usize,
+
+    /// Print to stdout how long each phase of `dfs::par` took for every tree.
+    pub timings: bool,
+
+    /// Sink for the progress reports of the traversal. With `None` they go
+    /// to the global sink, see `progress::set_sink`.
+    pub progress: Option<&'p dyn Progress>,
+}
+
+impl<'p> Default for DfsConfig<'p> {
+    fn default() -> Self {
+        DfsConfig {
+            pool: None,
+            sequential_cutoff: 64,
+            timings: false,
+            progress: None,
+        }
+    }
+}
+
+impl<'p> DfsConfig<'p> {
+    /// Run `op` on the configured pool.
+    pub(crate) fn install<R, F>(&self, op: F) -> R
+    where
+        F: FnOnce() -> R + Send,
+        R: Send,
+    {
+        match self.pool {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
+    }
+
+    /// Where the progress reports go.
+    pub(crate) fn sink(&self) -> Sink<'p> {
+        Sink::or_global(self.progress)
+    }
+}
+
+impl<'p> fmt::Debug for DfsConfig<'p> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DfsConfig")
+            .field("pool", &self.pool)
+            .field("sequential_cutoff", &self.sequential_cutoff)
+            .field("timings", &self.timings)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
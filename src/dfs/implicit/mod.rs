@@ -0,0 +1,206 @@
+//! DFS over implicit graphs
+//!
+//! Vertices are arbitrary states generated on the fly by a successor function,
+//! e.g. when exploring the state space of a model. Visited states are kept in
+//! a hash set instead of an array indexed by vertex id.
+
+use crossbeam::sync::SegQueue;
+use rayon::prelude::*;
+use rayon::Scope;
+
+use crate::progress::{self, Phase, Sink, Visits};
+use crate::utils::ShardedSet;
+
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::mem;
+
+/// Number of candidates on the stack of a task above which half of them
+/// are given to a new task.
+const SPLIT_AT: usize = 64;
+
+/// Traversal tree of an implicit graph.
+///
+/// The same as `Tree`, but for states. Edges are `(parent, child)` pairs.
+#[derive(Debug, Clone)]
+pub struct StateTree<S> {
+    pub root: S,
+    pub edges: Vec<(S, S)>,
+}
+
+/// Perform a sequential DFS traversal from each of the `initial` states.
+///
+/// `successors` returns the states reachable with one step from a state.
+/// Initial states which are reached from previous ones don't get a tree of
+/// their own, the same as vertices in `dfs::seq`.
+pub fn implicit_seq<S, F, I>(
+    initial: impl IntoIterator<Item = S>,
+    successors: F,
+) -> Vec<StateTree<S>>
+where
+    S: Hash + Eq + Clone,
+    F: Fn(&S) -> I,
+    I: IntoIterator<Item = S>,
+{
+    progress::phase(Phase::Traverse);
+
+    let mut visits = Visits::new(Sink::global());
+    let mut visited = HashSet::new();
+    let mut result = Vec::new();
+    let mut stack = Vec::new();
+
+    for root in initial {
+        if !visited.insert(root.clone()) {
+            continue;
+        }
+
+        let mut tree = StateTree {
+            root: root.clone(),
+            edges: Vec::new(),
+        };
+        visits.add();
+
+        push_successors(&successors, root, &visited, &mut stack);
+
+        while let Some((parent, state)) = stack.pop() {
+            if visited.insert(state.clone()) {
+                tree.edges.push((parent, state.clone()));
+                visits.add();
+
+                push_successors(&successors, state, &visited, &mut stack);
+            }
+        }
+
+        result.push(tree);
+    }
+
+    result
+}
+
+/// Perform a parallel traversal from each of the `initial` states.
+///
+/// Works like `dfs::cheat`: a state belongs to the tree, and the parent,
+/// which inserted it into the shared visited set first. Trees are grown from
+/// all initial states in parallel and each of them is split between tasks
+/// when the stack of a task grows long, so the result is a spanning forest,
+/// but not necessarily a DFS forest.
+pub fn implicit_par<S, F, I>(
+    initial: impl IntoIterator<Item = S>,
+    successors: F,
+) -> Vec<StateTree<S>>
+where
+    S: Hash + Eq + Clone + Send + Sync,
+    F: Fn(&S) -> I + Sync,
+    I: IntoIterator<Item = S>,
+{
+    progress::phase(Phase::Traverse);
+
+    let visited = ShardedSet::new();
+    let initial = initial.into_iter().collect::<Vec<_>>();
+
+    initial
+        .into_par_iter()
+        .filter_map(|root| {
+            if !visited.insert(root.clone()) {
+                return None;
+            }
+
+            progress::vertices_visited(1);
+
+            // Each task adds the edges it found when it finishes
+            let edges = SegQueue::new();
+            let mut stack = Vec::new();
+            push_successors(&successors, root.clone(), &visited, &mut stack);
+
+            rayon::scope(|scope| work(scope, &successors, &visited, &edges, stack));
+
+            let mut tree = StateTree {
+                root,
+                edges: Vec::new(),
+            };
+
+            while let Some(part) = edges.try_pop() {
+                tree.edges.extend(part);
+            }
+
+            Some(tree)
+        })
+        .collect()
+}
+
+/// Run DFS on the candidates in `stack`, giving away the bottom half of the
+/// stack to a new task whenever it gets longer than `SPLIT_AT`.
+fn work<'s, S, F, I>(
+    scope: &Scope<'s>,
+    successors: &'s F,
+    visited: &'s ShardedSet<S>,
+    edges: &'s SegQueue<Vec<(S, S)>>,
+    mut stack: Vec<(S, S)>,
+) where
+    S: Hash + Eq + Clone + Send + Sync,
+    F: Fn(&S) -> I + Sync,
+    I: IntoIterator<Item = S>,
+{
+    let mut visits = Visits::new(Sink::global());
+    let mut found = Vec::new();
+
+    while let Some((parent, state)) = stack.pop() {
+        if !visited.insert(state.clone()) {
+            continue;
+        }
+
+        found.push((parent, state.clone()));
+        visits.add();
+
+        push_successors(successors, state, visited, &mut stack);
+
+        if stack.len() > SPLIT_AT {
+            let rest = stack.split_off(stack.len() / 2);
+            let bottom = mem::replace(&mut stack, rest);
+
+            scope.spawn(move |scope| work(scope, successors, visited, edges, bottom));
+        }
+    }
+
+    edges.push(found);
+}
+
+/// Push the successors of `state` which aren't visited yet on the stack, so
+/// that they are popped in the order `successors` returns them.
+fn push_successors<S, F, I, V>(successors: &F, state: S, visited: &V, stack: &mut Vec<(S, S)>)
+where
+    S: Clone,
+    F: Fn(&S) -> I,
+    I: IntoIterator<Item = S>,
+    V: Visited<S>,
+{
+    let start = stack.len();
+
+    for next in successors(&state) {
+        if !visited.contains(&next) {
+            stack.push((state.clone(), next));
+        }
+    }
+
+    stack[start..].reverse();
+}
+
+/// Set of visited states, so that `push_successors` works with both versions.
+trait Visited<S> {
+    fn contains(&self, state: &S) -> bool;
+}
+
+impl<S: Hash + Eq> Visited<S> for HashSet<S> {
+    fn contains(&self, state: &S) -> bool {
+        HashSet::contains(self, state)
+    }
+}
+
+impl<S: Hash + Eq> Visited<S> for ShardedSet<S> {
+    fn contains(&self, state: &S) -> bool {
+        ShardedSet::contains(self, state)
+    }
+}
+
+#[cfg(test)]
+mod tests;
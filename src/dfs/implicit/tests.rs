@@ -0,0 +1,94 @@
+use rayon::ThreadPoolBuilder;
+
+use crate::dfs;
+use crate::graph::{AdjLists, GraphRef};
+use super::*;
+
+/// States of a counter modulo `n` with steps `+1` and `*2`.
+fn successors(n: u64) -> impl Fn(&u64) -> Vec<u64> + Sync {
+    move |&s| vec![(s + 1) % n, (s * 2) % n]
+}
+
+fn visited<S: Hash + Eq + Clone>(forest: &[StateTree<S>]) -> Vec<S> {
+    forest
+        .iter()
+        .flat_map(|tree| {
+            let children = tree.edges.iter().map(|(_, child)| child.clone());
+            Some(tree.root.clone()).into_iter().chain(children)
+        })
+        .collect()
+}
+
+#[test]
+fn visits_all() {
+    for forest in vec![
+        implicit_seq(vec![0], successors(10_000)),
+        implicit_par(vec![0], successors(10_000)),
+    ] {
+        let mut states = visited(&forest);
+        states.sort_unstable();
+
+        assert_eq!(states, (0..10_000).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn many_roots() {
+    // Only even states are reachable with `*2` and `+2`
+    let successors = |&s: &u64| vec![(s + 2) % 1000, (s * 2) % 1000];
+    let roots = vec![0, 2, 1, 3];
+
+    let forest = implicit_seq(roots.clone(), successors);
+    assert_eq!(forest.iter().map(|tree| tree.root).collect::<Vec<_>>(), vec![0, 1]);
+
+    let forest = implicit_par(roots, successors);
+    let mut states = visited(&forest);
+    states.sort_unstable();
+
+    assert_eq!(states, (0..1000).collect::<Vec<_>>());
+}
+
+// The sequential version should visit states in the same order as
+// `dfs::seq` on the same graph.
+#[test]
+fn matches_seq() {
+    let graph: AdjLists = AdjLists::gen_directed(100, 1000, None);
+
+    let answer = dfs::seq(&graph);
+    let forest = implicit_seq(graph.vertices(), |&v| graph.neighbours(v));
+
+    assert_eq!(forest.len(), answer.len());
+
+    for (tree, answer) in forest.iter().zip(&answer) {
+        assert_eq!(tree.root, answer.root);
+        assert_eq!(
+            tree.edges,
+            answer.edges.iter().map(|edge| (edge.from, edge.to)).collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn parent_is_visited_first() {
+    let thread_pool = ThreadPoolBuilder::new()
+        .num_threads(4)
+        .build()
+        .unwrap();
+
+    let forest = thread_pool.install(|| implicit_par(vec![0], successors(100_000)));
+    assert_eq!(forest.len(), 1);
+
+    let tree = &forest[0];
+    let mut seen = HashSet::new();
+    seen.insert(tree.root);
+
+    for &(_, child) in &tree.edges {
+        assert!(seen.insert(child));
+    }
+
+    // Every parent is either the root or a child of some edge
+    for &(parent, child) in &tree.edges {
+        assert!(seen.contains(&parent));
+        assert!(successors(100_000)(&parent).contains(&child));
+    }
+}
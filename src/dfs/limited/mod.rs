@@ -0,0 +1,186 @@
+//! Depth-limited and iterative deepening DFS
+
+use rayon::prelude::*;
+
+use crate::dfs::cheat::util::{take_ownership, Pool, State};
+use crate::graph::{Edge, GraphRef, Tree, VertexId};
+
+use std::sync::atomic::AtomicUsize;
+use std::usize;
+
+/// Result of a depth-limited traversal.
+#[derive(Debug, Clone)]
+pub struct Limited<V: VertexId = usize> {
+    /// The traversal forest. No vertex is more than `limit` edges away
+    /// from the root of its tree.
+    pub forest: Vec<Tree<V>>,
+    /// Vertices at depth `limit` which still had unvisited neighbours when
+    /// they were reached, i.e. the places where the traversal was cut short.
+    pub frontier: Vec<V>,
+}
+
+/// Perform a sequential DFS traversal of the graph which doesn't descend
+/// more than `limit` edges from the root of each tree.
+///
+/// Vertices that can't be reached within the limit become roots of new
+/// trees, the same way unreachable vertices do in `dfs::seq`.
+pub fn limited_seq<'a, G: GraphRef<'a> + Copy>(graph: G, limit: usize) -> Limited<G::Vertex> {
+    let mut forest = Vec::new();
+    let mut frontier = Vec::new();
+    let mut used = vec![false; graph.n_verts()];
+    let mut stack = Vec::new();
+
+    for root in graph.vertices() {
+        if used[root.index()] {
+            continue;
+        }
+
+        let mut tree = Tree::new(root);
+        used[root.index()] = true;
+
+        expand_seq(graph, root, 0, limit, &used, &mut stack, &mut frontier);
+
+        while let Some((parent, v, depth)) = stack.pop() {
+            if !used[v.index()] {
+                used[v.index()] = true;
+                tree.add(Edge::new(parent, v));
+
+                expand_seq(graph, v, depth, limit, &used, &mut stack, &mut frontier);
+            }
+        }
+
+        forest.push(tree);
+    }
+
+    Limited { forest, frontier }
+}
+
+/// Push the unused neighbours of `v` on the stack, or record `v` as
+/// a frontier vertex if it is already at the depth limit.
+fn expand_seq<'a, G: GraphRef<'a> + Copy>(
+    graph: G,
+    v: G::Vertex,
+    depth: usize,
+    limit: usize,
+    used: &[bool],
+    stack: &mut Vec<(G::Vertex, G::Vertex, usize)>,
+    frontier: &mut Vec<G::Vertex>,
+) {
+    if depth < limit {
+        for child in graph.neighbours(v).rev() {
+            if !used[child.index()] {
+                stack.push((v, child, depth + 1));
+            }
+        }
+    } else if graph.neighbours(v).any(|child| !used[child.index()]) {
+        frontier.push(v);
+    }
+}
+
+/// Perform a parallel depth-limited DFS traversal of the graph.
+///
+/// Trees are grown in parallel from every vertex the same way `dfs::cheat`
+/// does it, but no tree grows deeper than `limit` edges. Ownership of the
+/// neighbours of a vertex at the limit isn't taken, so they remain free for
+/// other trees.
+pub fn limited_par<'a, G: GraphRef<'a> + Copy + Sync>(
+    graph: G,
+    limit: usize,
+) -> Limited<G::Vertex> {
+    // Same as in `dfs::cheat`, the value is the root of the tree that owns the
+    // vertex or `usize::MAX` if the vertex isn't owned.
+    let owner = (0..graph.n_verts())
+        .map(|_| AtomicUsize::new(usize::MAX))
+        .collect::<Vec<_>>();
+
+    let pool = Pool::new();
+
+    let (forest, frontier): (Vec<_>, Vec<_>) = graph
+        .vertices_par()
+        .filter_map(|root| {
+            if !take_ownership(&owner[root.index()], root.index()) {
+                return None;
+            }
+
+            let mut stack = Vec::new();
+            let mut frontier = Vec::new();
+            let mut state = State::new(root.index(), &owner, &pool);
+            let mut tree = Tree::new(root);
+
+            expand_par(graph, root, 0, limit, &mut state, &mut stack, &mut frontier);
+
+            while let Some((parent, v, depth)) = stack.pop() {
+                if state.get(v.index()).is_owned_unused() {
+                    state.mark_used(v.index());
+                    tree.add(Edge::new(parent, v));
+
+                    expand_par(graph, v, depth, limit, &mut state, &mut stack, &mut frontier);
+                }
+            }
+
+            Some((tree, frontier))
+        })
+        .unzip();
+
+    Limited {
+        forest,
+        frontier: frontier.into_iter().flatten().collect(),
+    }
+}
+
+/// Same as `expand_seq`, but checks the neighbours through the tree's `State`.
+fn expand_par<'a, G: GraphRef<'a> + Copy>(
+    graph: G,
+    v: G::Vertex,
+    depth: usize,
+    limit: usize,
+    state: &mut State,
+    stack: &mut Vec<(G::Vertex, G::Vertex, usize)>,
+    frontier: &mut Vec<G::Vertex>,
+) {
+    if depth < limit {
+        for child in graph.neighbours(v).rev() {
+            if state.get(child.index()).is_owned_unused() {
+                stack.push((v, child, depth + 1));
+            }
+        }
+    } else if graph.neighbours(v).any(|child| state.is_unvisited(child.index())) {
+        frontier.push(v);
+    }
+}
+
+/// Iterative deepening DFS.
+///
+/// Runs `limited_seq` with limits `0, 1, 2, ...` until either the frontier is
+/// empty, i.e. the limit didn't cut anything off, or `max_limit` is reached.
+/// Returns the result of the last run.
+pub fn deepening_seq<'a, G: GraphRef<'a> + Copy>(graph: G, max_limit: usize) -> Limited<G::Vertex> {
+    deepening(max_limit, |limit| limited_seq(graph, limit))
+}
+
+/// Iterative deepening DFS using `limited_par` for each iteration.
+///
+/// See `deepening_seq`.
+pub fn deepening_par<'a, G: GraphRef<'a> + Copy + Sync>(
+    graph: G,
+    max_limit: usize,
+) -> Limited<G::Vertex> {
+    deepening(max_limit, |limit| limited_par(graph, limit))
+}
+
+fn deepening<V: VertexId, F: Fn(usize) -> Limited<V>>(max_limit: usize, run: F) -> Limited<V> {
+    let mut limit = 0;
+
+    loop {
+        let result = run(limit);
+
+        if result.frontier.is_empty() || limit >= max_limit {
+            return result;
+        }
+
+        limit += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,84 @@
+use rayon::ThreadPoolBuilder;
+
+use crate::dfs;
+use crate::graph::{tree, AdjLists, AdjMatrix, GraphRef, Tree};
+use super::*;
+
+/// Depth of the deepest vertex in the tree.
+fn height(tree: &Tree) -> usize {
+    let mut depth = std::collections::HashMap::new();
+    depth.insert(tree.root, 0);
+
+    // Edges are added to a tree in traversal order, so the parent of every
+    // edge is already known.
+    for edge in &tree.edges {
+        let d = depth[&edge.from] + 1;
+        depth.insert(edge.to, d);
+    }
+
+    depth.values().cloned().max().unwrap()
+}
+
+#[test]
+fn respects_limit() {
+    let graph: AdjLists = AdjLists::gen_directed(100, 300, None);
+
+    for limit in 0..5 {
+        let result = limited_seq(&graph, limit);
+        assert!(tree::is_spanning(100, &result.forest));
+        assert!(result.forest.iter().all(|tree| height(tree) <= limit));
+
+        let result = limited_par(&graph, limit);
+        assert!(tree::is_spanning(100, &result.forest));
+        assert!(result.forest.iter().all(|tree| height(tree) <= limit));
+    }
+}
+
+#[test]
+fn frontier_at_limit() {
+    let graph = AdjMatrix::gen_directed(100, 300, None);
+    let result = limited_seq(&graph, 0);
+
+    // With a limit of 0 every vertex is its own tree and is cut off
+    // if any of its neighbours comes after it.
+    assert_eq!(result.forest.len(), 100);
+    assert!(!result.frontier.is_empty());
+    assert!(result.frontier.iter().all(|&v| graph.neighbours(v).any(|u| u > v)));
+}
+
+// Without a binding limit the result should be the same as `dfs::seq`.
+#[test]
+fn unlimited_matches_seq() {
+    let graph: AdjLists = AdjLists::gen_directed(100, 1000, None);
+    let answer = dfs::seq(&graph);
+    let result = limited_seq(&graph, 100);
+
+    assert!(result.frontier.is_empty());
+    assert_eq!(result.forest.len(), answer.len());
+
+    for (tree, answer) in result.forest.iter().zip(&answer) {
+        assert_eq!(tree.root, answer.root);
+        assert_eq!(tree.edges, answer.edges);
+    }
+}
+
+#[test]
+fn deepening_finishes() {
+    let graph: AdjLists = AdjLists::gen_directed(100, 1000, None);
+    let answer = dfs::seq(&graph);
+
+    let result = deepening_seq(&graph, 100);
+    assert!(result.frontier.is_empty());
+    assert_eq!(result.forest.len(), answer.len());
+
+    let thread_pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+
+    thread_pool.install(|| {
+        let result = deepening_par(&graph, 100);
+        assert!(result.frontier.is_empty());
+        assert!(tree::is_spanning(100, &result.forest));
+    });
+}
@@ -1,7 +1,29 @@
+mod algorithm;
+mod cancel;
 mod cheat;
+mod config;
+mod implicit;
+mod limited;
 mod par;
+mod repair;
 mod seq;
+mod steal;
 
+pub use self::algorithm::Algorithm;
+pub use self::cancel::{CancellationToken, Traversal};
 pub use self::cheat::run as cheat;
+pub use self::cheat::run_cancellable as cheat_cancellable;
+pub use self::cheat::run_cancellable_with as cheat_cancellable_with;
+pub use self::cheat::run_with as cheat_with;
+pub use self::config::DfsConfig;
+pub use self::implicit::{implicit_par, implicit_seq, StateTree};
+pub use self::limited::{deepening_par, deepening_seq, limited_par, limited_seq, Limited};
 pub use self::par::run as par;
+pub use self::par::run_cancellable as par_cancellable;
+pub use self::par::run_cancellable_with as par_cancellable_with;
+pub use self::par::run_with as par_with;
+pub use self::repair::{repair, repair_with, RepairStats, Repaired};
 pub use self::seq::run as seq;
+pub use self::seq::run_with as seq_with;
+pub use self::steal::run as steal;
+pub use self::steal::run_with as steal_with;
@@ -0,0 +1,61 @@
+//! Integer types for the `owner` array of `dfs::par`
+
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+/// Unsigned integer wide enough to hold every vertex and backtrack index of
+/// the graph being traversed.
+///
+/// Graphs with less than `u32::MAX` vertices use `u32`, which halves the
+/// memory needed for `owner` and the backtrack stack. Bigger graphs use `u64`.
+pub trait Index: Copy + Ord + Send + Sync {
+    type Atomic: Send + Sync;
+
+    /// Value of `owner` for vertices which aren't visited yet. Larger than
+    /// any valid index.
+    const NOT_VISITED: Self;
+
+    fn from_usize(value: usize) -> Self;
+
+    fn new_atomic(value: Self) -> Self::Atomic;
+    fn load(atomic: &Self::Atomic) -> Self;
+
+    /// Replace the value of `atomic` with `new` if the current one is larger.
+    /// Returns whether it was replaced.
+    fn take(atomic: &Self::Atomic, new: Self) -> bool;
+}
+
+macro_rules! impl_index {
+    ($T: ident, $Atomic: ident) => {
+        impl Index for $T {
+            type Atomic = $Atomic;
+
+            const NOT_VISITED: $T = $T::max_value();
+
+            fn from_usize(value: usize) -> Self {
+                debug_assert!((value as u64) < $T::max_value() as u64);
+                value as $T
+            }
+
+            fn new_atomic(value: Self) -> $Atomic {
+                $Atomic::new(value)
+            }
+
+            fn load(atomic: &$Atomic) -> Self {
+                atomic.load(Ordering::SeqCst)
+            }
+
+            fn take(atomic: &$Atomic, new: Self) -> bool {
+                atomic
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |found| match found > new {
+                        true => Some(new),
+                        false => None,
+                    })
+                    .is_ok()
+            }
+        }
+    };
+}
+
+impl_index!(u32, AtomicU32);
+impl_index!(u64, AtomicU64);
+
@@ -1,46 +1,104 @@
 use rayon::prelude::*;
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::mem;
+use std::time::Instant;
 
-use crate::graph::{Edge, GraphRef, Tree};
+use crate::dfs::cancel::{CancellationToken, Checker, Traversal};
+use crate::dfs::config::DfsConfig;
+use crate::graph::{Edge, GraphRef, Tree, VertexId};
+use crate::progress::{Phase, Sink, Visits};
 
-pub fn run<'a, G: GraphRef<'a> + Copy + Sync>(graph: G) -> Vec<Tree> {
-    const NOT_VISITED: u32 = u32::max_value();
+mod index;
+use self::index::Index;
 
-    let n_verts = graph.vertices().count();
-    assert!(n_verts < u32::max_value() as usize);
+pub fn run<'a, G: GraphRef<'a> + Copy + Sync>(graph: G) -> Vec<Tree<G::Vertex>> {
+    run_inner(graph, &DfsConfig::default(), &CancellationToken::new()).into_forest()
+}
+
+/// Same as `run`, but with custom options.
+pub fn run_with<'a, G: GraphRef<'a> + Copy + Send + Sync>(
+    graph: G,
+    config: &DfsConfig,
+) -> Vec<Tree<G::Vertex>> {
+    run_cancellable_with(graph, config, &CancellationToken::new()).into_forest()
+}
+
+/// Same as `run`, but stops when `token` is cancelled.
+///
+/// Both the descend and the backtrack phase are cut short and no new
+/// trees are started.
+pub fn run_cancellable<'a, G: GraphRef<'a> + Copy + Sync>(
+    graph: G,
+    token: &CancellationToken,
+) -> Traversal<G::Vertex> {
+    run_inner(graph, &DfsConfig::default(), token)
+}
+
+/// Same as `run_cancellable`, but with custom options.
+pub fn run_cancellable_with<'a, G: GraphRef<'a> + Copy + Send + Sync>(
+    graph: G,
+    config: &DfsConfig,
+    token: &CancellationToken,
+) -> Traversal<G::Vertex> {
+    config.install(|| run_inner(graph, config, token))
+}
 
+fn run_inner<'a, G: GraphRef<'a> + Copy + Sync>(
+    graph: G,
+    config: &DfsConfig,
+    token: &CancellationToken,
+) -> Traversal<G::Vertex> {
+    let n_verts = graph.n_verts();
+
+    match n_verts < u32::max_value() as usize {
+        true => run_indexed::<G, u32>(graph, n_verts, config, token),
+        false => run_indexed::<G, u64>(graph, n_verts, config, token),
+    }
+}
+
+/// The actual algorithm, with `owner` and the backtrack indices stored as `I`.
+fn run_indexed<'a, G: GraphRef<'a> + Copy + Sync, I: Index>(
+    graph: G,
+    n_verts: usize,
+    config: &DfsConfig,
+    token: &CancellationToken,
+) -> Traversal<G::Vertex> {
     let mut owner = Vec::with_capacity(n_verts);
-    owner.resize_with(n_verts, || AtomicU32::new(NOT_VISITED));
+    owner.resize_with(n_verts, || I::new_atomic(I::NOT_VISITED));
 
     let mut backtrack_start_index = 0;
 
-    graph
+    let forest = graph
         .vertices()
-        .filter(|&root| owner[root].load(Ordering::SeqCst) == NOT_VISITED)
+        .filter(|&root| I::load(&owner[root.index()]) == I::NOT_VISITED)
+        .take_while(|_| !token.is_cancelled())
         .map(|root| {
-            assert!(take(&owner[root], backtrack_start_index as u32));
+            assert!(I::take(&owner[root.index()], I::from_usize(backtrack_start_index)));
 
-            let start = std::time::Instant::now();
+            let start = Instant::now();
 
             let (mut descend_tree, mut backtrack_stack) =
-                descend(graph, &owner, root as u32, backtrack_start_index);
+                descend::<_, I>(graph, &owner, root, backtrack_start_index, config.sink(), token);
 
-            let after_descend = std::time::Instant::now();
-            println!("    descend: {:?} (depth: {})", after_descend.duration_since(start), backtrack_stack.len());
+            let after_descend = Instant::now();
+            if config.timings {
+                println!("    descend: {:?} (depth: {})", after_descend.duration_since(start), backtrack_stack.len());
+            }
 
             backtrack_stack.pop();
             backtrack_start_index += 1;
 
-            let mut backtrack = backtrack(graph, &owner, &backtrack_stack, backtrack_start_index);
+            let mut backtrack = backtrack::<_, I>(graph, &owner, &backtrack_stack, backtrack_start_index, config, token);
 
-            let after_backtrack = std::time::Instant::now();
-            println!("    backtrack: {:?}", after_backtrack.duration_since(after_descend));
+            let after_backtrack = Instant::now();
+            if config.timings {
+                println!("    backtrack: {:?}", after_backtrack.duration_since(after_descend));
+            }
 
             backtrack
                 .par_iter_mut()
                 .for_each(|(backtrack_index, ref mut tree)| {
                     tree.edges
-                        .retain(|edge| owner[edge.to].load(Ordering::SeqCst) == *backtrack_index)
+                        .retain(|edge| I::load(&owner[edge.to.index()]) == *backtrack_index)
                 });
 
 
@@ -48,104 +106,170 @@ pub fn run<'a, G: GraphRef<'a> + Copy + Sync>(graph: G) -> Vec<Tree> {
                 descend_tree.edges.extend(tree.edges);
             });
 
-            let after_post_process = std::time::Instant::now();
-            println!("    post process: {:?}", after_post_process.duration_since(after_backtrack));
+            if config.timings {
+                let after_post_process = Instant::now();
+                println!("    post process: {:?}", after_post_process.duration_since(after_backtrack));
+            }
 
             backtrack_start_index += backtrack_stack.len();
             descend_tree
         })
-        .collect()
-}
+        .collect();
 
-fn take(owner: &AtomicU32, new: u32) -> bool {
-    owner
-        .fetch_update(
-            |found| match found > new {
-                true => Some(new),
-                false => None,
-            },
-            Ordering::SeqCst,
-            Ordering::SeqCst,
-        )
-        .is_ok()
+    Traversal::new(forest, n_verts, token)
 }
 
-fn descend<'a, G: GraphRef<'a> + Copy + Sync>(
+fn descend<'a, G: GraphRef<'a> + Copy + Sync, I: Index>(
     graph: G,
-    owner: &[AtomicU32],
-    root: u32,
+    owner: &[I::Atomic],
+    root: G::Vertex,
     backtrack_start_index: usize,
-) -> (Tree, Vec<u32>) {
-    let n_verts = graph.vertices().count();
+    sink: Sink,
+    token: &CancellationToken,
+) -> (Tree<G::Vertex>, Vec<G::Vertex>) {
+    sink.phase(Phase::Descend);
 
-    let mut tree = Tree::new(root as usize);
-    let mut used = vec![false; n_verts];
-    let mut backtrack_stack = Vec::with_capacity(n_verts);
+    let mut visits = Visits::new(sink);
+    let mut tree = Tree::new(root);
+    let mut backtrack_stack = Vec::new();
 
+    visits.add();
+
+    let mut checker = Checker::new(token);
     let mut parent = root;
 
+    // Vertices already visited in this phase are tagged with
+    // `backtrack_start_index`, so `take` fails for them.
     while let Some(child) = graph
-        .neighbours(parent as usize)
-        .filter(|&v| !used[v] && take(&owner[v], backtrack_start_index as u32))
+        .neighbours(parent)
+        .filter(|&v| I::take(&owner[v.index()], I::from_usize(backtrack_start_index)))
         .next()
     {
-        used[child] = true;
-        tree.add(Edge::new(parent as usize, child));
-        backtrack_stack.push(child as u32);
+        tree.add(Edge::new(parent, child));
+        visits.add();
+        backtrack_stack.push(child);
+
+        parent = child;
 
-        parent = child as u32;
+        if checker.is_cancelled() {
+            break;
+        }
     }
 
     (tree, backtrack_stack)
 }
 
-fn backtrack<'a, G: GraphRef<'a> + Copy + Sync>(
+fn backtrack<'a, G: GraphRef<'a> + Copy + Sync, I: Index>(
     graph: G,
-    owner: &[AtomicU32],
-    backtrack_stack: &[u32],
+    owner: &[I::Atomic],
+    backtrack_stack: &[G::Vertex],
     backtrack_start_index: usize,
-) -> Vec<(u32, Tree)> {
-    let n_verts = graph.vertices().count();
-
-    backtrack_stack
+    config: &DfsConfig,
+    token: &CancellationToken,
+) -> Vec<(I, Tree<G::Vertex>)> {
+    let sink = config.sink();
+    sink.phase(Phase::Backtrack);
+
+    // The deepest node gets the smallest index
+    let nodes = backtrack_stack
         .par_iter()
         .rev()
         .enumerate()
-        .filter_map(|(backtrack_index, &node)| {
-            let backtrack_index = (backtrack_index + backtrack_start_index) as u32;
-
-            let mut used = vec![false; n_verts];
-            let mut stack = Vec::new();
-            let mut tree = Tree::new(node as usize);
+        .map(|(i, &node)| (node, I::from_usize(i + backtrack_start_index)))
+        .collect::<Vec<_>>();
 
+    // Estimate how much work hangs off each node by the number of
+    // neighbours it may still take
+    let weights = nodes
+        .par_iter()
+        .map(|&(node, backtrack_index)| {
             graph
-                .neighbours(node as usize)
-                .rev()
-                .filter(|&v| owner[v].load(Ordering::SeqCst) >= backtrack_index)
-                .for_each(|v| stack.push((node, v)));
-
-            while let Some((parent, child)) = stack.pop() {
-                if used[child as usize] || !take(&owner[child as usize], backtrack_index) {
-                    continue;
-                }
-
-                used[child as usize] = true;
-                tree.add(Edge::new(parent as usize, child as usize));
-
-                graph
-                    .neighbours(child as usize)
-                    .rev()
-                    .filter(|&v| owner[v].load(Ordering::SeqCst) >= backtrack_index)
-                    .for_each(|v| stack.push((child as u32, v)));
-            }
+                .neighbours(node)
+                .filter(|&v| I::load(&owner[v.index()]) > backtrack_index)
+                .count()
+        })
+        .collect::<Vec<_>>();
 
-            match tree.edges.is_empty() {
-                true => None,
-                false => Some((backtrack_index, tree)),
-            }
+    let mut groups = Vec::new();
+    let mut group = Vec::new();
+    let mut group_weight = 0;
+
+    for (node, weight) in nodes.into_iter().zip(weights) {
+        if !group.is_empty() && group_weight + weight > config.sequential_cutoff {
+            groups.push(mem::replace(&mut group, Vec::new()));
+            group_weight = 0;
+        }
+
+        group.push(node);
+        group_weight += weight;
+    }
+
+    if !group.is_empty() {
+        groups.push(group);
+    }
+
+    groups
+        .par_iter()
+        .flat_map(|group| {
+            let mut checker = Checker::new(token);
+            let mut visits = Visits::new(sink);
+
+            group
+                .iter()
+                .filter_map(|&(node, backtrack_index)| {
+                    backtrack_node::<_, I>(graph, owner, node, backtrack_index, &mut checker, &mut visits)
+                })
+                .collect::<Vec<_>>()
         })
         .collect()
 }
 
+/// Traverse everything reachable from `node` that isn't taken by
+/// a node with a smaller index.
+fn backtrack_node<'a, G: GraphRef<'a> + Copy + Sync, I: Index>(
+    graph: G,
+    owner: &[I::Atomic],
+    node: G::Vertex,
+    backtrack_index: I,
+    checker: &mut Checker,
+    visits: &mut Visits,
+) -> Option<(I, Tree<G::Vertex>)> {
+    let mut stack = Vec::new();
+    let mut tree = Tree::new(node);
+
+    graph
+        .neighbours(node)
+        .rev()
+        .filter(|&v| I::load(&owner[v.index()]) >= backtrack_index)
+        .for_each(|v| stack.push((node, v)));
+
+    while let Some((parent, child)) = stack.pop() {
+        if checker.is_cancelled() {
+            break;
+        }
+
+        // Vertices this task already visited are tagged with its
+        // `backtrack_index`, so `take` fails for them, the same
+        // as for vertices taken by a task with a smaller index.
+        if !I::take(&owner[child.index()], backtrack_index) {
+            continue;
+        }
+
+        tree.add(Edge::new(parent, child));
+        visits.add();
+
+        graph
+            .neighbours(child)
+            .rev()
+            .filter(|&v| I::load(&owner[v.index()]) >= backtrack_index)
+            .for_each(|v| stack.push((child, v)));
+    }
+
+    match tree.edges.is_empty() {
+        true => None,
+        false => Some((backtrack_index, tree)),
+    }
+}
+
 #[cfg(test)]
 mod tests;
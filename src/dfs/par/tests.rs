@@ -3,7 +3,7 @@
 use rayon::ThreadPoolBuilder;
 
 use crate::dfs;
-use crate::graph::AdjMatrix;
+use crate::graph::{AdjLists, AdjMatrix, Forest};
 use super::*;
 
 #[test]
@@ -51,3 +51,148 @@ fn matches_seq() {
         }
     });
 }
+
+#[test]
+fn cancelled() {
+    let graph = AdjMatrix::gen_directed(100, 1000, None);
+
+    let token = dfs::CancellationToken::with_deadline(std::time::Instant::now());
+
+    match dfs::par_cancellable(&graph, &token) {
+        dfs::Traversal::Partial { forest, unvisited } => {
+            assert!(forest.is_empty());
+            assert_eq!(unvisited.len(), 100);
+        },
+        dfs::Traversal::Complete(_) => panic!("traversal should be cancelled"),
+    }
+
+    let token = dfs::CancellationToken::new();
+    assert!(dfs::par_cancellable(&graph, &token).is_complete());
+}
+
+// Cancellation works together with the other options.
+#[test]
+fn cancelled_with_config() {
+    let graph = AdjMatrix::gen_directed(300, 3000, None);
+    let pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+    let config = DfsConfig {
+        pool: Some(&pool),
+        sequential_cutoff: 0,
+        ..DfsConfig::default()
+    };
+
+    let token = dfs::CancellationToken::new();
+    token.cancel();
+    assert!(!dfs::par_cancellable_with(&graph, &config, &token).is_complete());
+
+    let token = dfs::CancellationToken::new();
+    let forest = dfs::par_cancellable_with(&graph, &config, &token).into_forest();
+    assert_eq!(forest.parent_array(), dfs::seq(&graph).parent_array());
+}
+
+#[test]
+fn sequential_cutoff() {
+    let graph = AdjMatrix::gen_directed(300, 3000, None);
+
+    for &sequential_cutoff in &[0, 1, 64, usize::max_value()] {
+        let forest = dfs::par_with(&graph, &dfs::DfsConfig {
+            sequential_cutoff,
+            ..dfs::DfsConfig::default()
+        });
+
+        let mut visited = vec![0; graph.vertices().count()];
+
+        for tree in &forest {
+            visited[tree.root] += 1;
+
+            for edge in &tree.edges {
+                visited[edge.to] += 1;
+            }
+        }
+
+        assert!(visited.iter().all(|&v| v == 1));
+    }
+}
+
+// The index width must not change the result. Graphs large enough to need
+// `u64` don't fit in a test, so force it on a small one.
+#[test]
+fn wide_index() {
+    let graph = AdjMatrix::gen_directed(300, 3000, None);
+    let n_verts = graph.vertices().count();
+    let config = DfsConfig::default();
+    let token = CancellationToken::new();
+
+    let thread_pool = ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+
+    let (narrow, wide) = thread_pool.install(|| {
+        (
+            run_indexed::<_, u32>(&graph, n_verts, &config, &token).into_forest(),
+            run_indexed::<_, u64>(&graph, n_verts, &config, &token).into_forest(),
+        )
+    });
+
+    assert_eq!(narrow.len(), wide.len());
+
+    for (narrow, wide) in narrow.iter().zip(&wide) {
+        assert_eq!(narrow.root, wide.root);
+        assert_eq!(narrow.edges, wide.edges);
+    }
+}
+
+#[test]
+fn narrow_vertex_id() {
+    let thread_pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+
+    thread_pool.install(|| {
+        let graph: AdjLists<u32> = AdjLists::gen_directed(100, 1000, None);
+        let mut answer = dfs::seq(&graph);
+        let mut forest = dfs::par(&graph);
+
+        answer.sort_unstable_by_key(|tree| tree.root);
+        forest.sort_unstable_by_key(|tree| tree.root);
+        assert_eq!(forest.len(), answer.len());
+
+        for (tree, answer) in forest.iter_mut().zip(&mut answer) {
+            tree.edges.sort_unstable_by_key(|edge| edge.from);
+            answer.edges.sort_unstable_by_key(|edge| edge.from);
+
+            assert_eq!(tree.root, answer.root);
+            assert_eq!(tree.edges, answer.edges);
+        }
+    });
+}
+
+// Same as `matches_seq`, but with the pool passed in the config instead of
+// installed by the caller.
+#[test]
+fn pool() {
+    let thread_pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+
+    let config = DfsConfig {
+        pool: Some(&thread_pool),
+        ..DfsConfig::default()
+    };
+
+    let graph = AdjMatrix::gen_directed(100, 1000, None);
+    let mut answer = dfs::seq(&graph);
+    let mut forest = dfs::par_with(&graph, &config);
+
+    answer.sort_unstable_by_key(|tree| tree.root);
+    forest.sort_unstable_by_key(|tree| tree.root);
+    assert_eq!(forest.len(), answer.len());
+
+    for (tree, answer) in forest.iter_mut().zip(&mut answer) {
+        tree.edges.sort_unstable_by_key(|edge| edge.from);
+        answer.edges.sort_unstable_by_key(|edge| edge.from);
+
+        assert_eq!(tree.root, answer.root);
+        assert_eq!(tree.edges, answer.edges);
+    }
+}
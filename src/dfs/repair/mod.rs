@@ -0,0 +1,471 @@
+//! Repair of spanning forests into DFS forests
+
+use rayon::prelude::*;
+
+use crate::dfs::config::DfsConfig;
+use crate::graph::{Edge, Forest, GraphRef, Tree, VertexId};
+use crate::progress::{Phase, Visits};
+
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Marks a missing index.
+const NONE: usize = usize::MAX;
+
+/// Result of `repair`.
+#[derive(Debug, Clone)]
+pub struct Repaired<V: VertexId = usize> {
+    /// DFS forest of the graph, with the trees in the order they would be
+    /// traversed.
+    pub forest: Vec<Tree<V>>,
+    pub stats: RepairStats,
+}
+
+/// How much of the forest had to be changed by `repair`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepairStats {
+    /// Edges inside a tree which break the DFS property, i.e. which lead to
+    /// a vertex discovered after the tree was done with the edge's source.
+    pub violations: usize,
+    /// Trees with at least one such edge.
+    pub broken_trees: usize,
+    /// Trees with edges going both ways to other trees, directly or through
+    /// further trees, so that there is no order in which they can be visited.
+    pub tangled_trees: usize,
+    /// Trees with at least one vertex which was grafted somewhere else.
+    pub retraversed_trees: usize,
+    /// Vertices which got a different parent, because an edge which breaks
+    /// the DFS property or one of the vertices moved with it found them
+    /// first.
+    pub retraversed_vertices: usize,
+}
+
+/// Turn a spanning forest of `graph`, such as the one from `dfs::cheat`, into
+/// a DFS forest.
+///
+/// The children of each vertex are taken in the order they appear among its
+/// neighbours, as in `Forest::numbering`. The trees are first put in an order
+/// where the edges between them go to earlier trees. Trees which can't be
+/// ordered like that, because there are edges both ways between them, keep
+/// the order of the forest.
+///
+/// Then every edge which leads to a vertex discovered after its source was
+/// done breaks the DFS property. The trees with such edges are traversed
+/// again, which follows the tree edges wherever it can, so only the subtrees
+/// below those edges are grafted where a DFS would find them. Independent
+/// groups of trees are repaired in parallel.
+///
+/// ```ignore
+/// let repaired = dfs::repair(&graph, dfs::cheat(&graph));
+/// println!("{:?}", repaired.stats);
+/// ```
+///
+/// Every vertex of the graph must be in exactly one tree.
+pub fn repair<'a, G>(graph: G, forest: Vec<Tree<G::Vertex>>) -> Repaired<G::Vertex>
+where
+    G: GraphRef<'a> + Copy + Sync,
+{
+    repair_inner(graph, forest, &DfsConfig::default())
+}
+
+/// Same as `repair`, but with custom options.
+pub fn repair_with<'a, G>(
+    graph: G,
+    forest: Vec<Tree<G::Vertex>>,
+    config: &DfsConfig,
+) -> Repaired<G::Vertex>
+where
+    G: GraphRef<'a> + Copy + Send + Sync,
+{
+    config.install(|| repair_inner(graph, forest, config))
+}
+
+fn repair_inner<'a, G>(
+    graph: G,
+    forest: Vec<Tree<G::Vertex>>,
+    config: &DfsConfig,
+) -> Repaired<G::Vertex>
+where
+    G: GraphRef<'a> + Copy + Sync,
+{
+    let sink = config.sink();
+    sink.phase(Phase::Repair);
+
+    let n_verts = graph.n_verts();
+    let n_trees = forest.len();
+    let tree_of = tree_of(&forest, n_verts);
+
+    // `(from, to)` means that tree `to` has to be visited before `from`
+    let mut before = graph
+        .edges_par()
+        .map(|edge| (tree_of[edge.from.index()], tree_of[edge.to.index()]))
+        .filter(|&(from, to)| from != to)
+        .collect::<Vec<_>>();
+
+    before.par_sort_unstable();
+    before.dedup();
+
+    let mut offsets = vec![0; n_trees + 1];
+
+    for &(from, _) in &before {
+        offsets[from + 1] += 1;
+    }
+
+    for t in 0..n_trees {
+        offsets[t + 1] += offsets[t];
+    }
+
+    let before = before.into_iter().map(|(_, to)| to).collect::<Vec<_>>();
+    let components = components(&offsets, &before);
+
+    let mut component_of_tree = vec![0; n_trees];
+
+    for (c, component) in components.iter().enumerate() {
+        for &t in component {
+            component_of_tree[t] = c;
+        }
+    }
+
+    let component = tree_of
+        .par_iter()
+        .map(|&t| component_of_tree[t])
+        .collect::<Vec<_>>();
+
+    // The trees in the order of their components
+    let mut forest = forest.into_iter().map(Some).collect::<Vec<_>>();
+    let ordered = components
+        .iter()
+        .flatten()
+        .map(|&t| forest[t].take().unwrap())
+        .collect::<Vec<_>>();
+
+    let numbering = ordered.numbering(graph);
+    let pre = numbering.pre.into_par_iter().map(Option::unwrap).collect::<Vec<_>>();
+    let post = numbering.post.into_par_iter().map(Option::unwrap).collect::<Vec<_>>();
+
+    // Edges which break the DFS property. In this order, the ones between
+    // trees only go to later trees of the same component.
+    let broken = (0..n_trees)
+        .map(|_| AtomicBool::new(false))
+        .collect::<Vec<_>>();
+    let needs_repair = (0..components.len())
+        .map(|_| AtomicBool::new(false))
+        .collect::<Vec<_>>();
+    let violations = AtomicUsize::new(0);
+
+    graph.edges_par().for_each(|edge| {
+        let (u, v) = (edge.from.index(), edge.to.index());
+
+        if pre[u] < pre[v] && post[u] < post[v] {
+            needs_repair[component[v]].store(true, Ordering::Relaxed);
+
+            if tree_of[u] == tree_of[v] {
+                violations.fetch_add(1, Ordering::Relaxed);
+                broken[tree_of[u]].store(true, Ordering::Relaxed);
+            }
+        }
+    });
+
+    let parents = ordered.parent_array();
+    let sizes = ordered.subtree_sizes();
+
+    let order = Order {
+        component: &component,
+        parents: &parents,
+        pre: &pre,
+        sizes: &sizes,
+    };
+
+    let mut starts = Vec::with_capacity(components.len());
+    let mut start = 0;
+
+    for component in &components {
+        starts.push(start);
+        start += component.len();
+    }
+
+    let parts = components
+        .par_iter()
+        .zip(starts)
+        .enumerate()
+        .map(|(c, (trees, start))| match needs_repair[c].load(Ordering::Relaxed) {
+            true => {
+                let trees = &ordered[start..start + trees.len()];
+                let n_verts = trees.iter().map(|tree| tree.edges.len() + 1).sum();
+                let roots = trees.iter().map(|tree| tree.root);
+                Some(regraft(graph, &order, c, n_verts, roots, &mut Visits::new(sink)))
+            },
+            false => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut stats = RepairStats {
+        violations: violations.into_inner(),
+        broken_trees: broken.into_iter().filter(|broken| broken.load(Ordering::Relaxed)).count(),
+        ..RepairStats::default()
+    };
+
+    let mut retraversed_trees = vec![false; n_trees];
+    let mut ordered = ordered.into_iter();
+    let mut result = Vec::with_capacity(n_trees);
+
+    for (trees, part) in components.iter().zip(parts) {
+        if trees.len() > 1 {
+            stats.tangled_trees += trees.len();
+        }
+
+        let kept = ordered.by_ref().take(trees.len());
+
+        match part {
+            Some((part, regrafted)) => {
+                for v in regrafted {
+                    retraversed_trees[tree_of[v]] = true;
+                    stats.retraversed_vertices += 1;
+                }
+
+                kept.for_each(drop);
+                result.extend(part);
+            },
+            None => result.extend(kept),
+        }
+    }
+
+    stats.retraversed_trees = retraversed_trees.into_iter().filter(|&t| t).count();
+
+    Repaired {
+        forest: result,
+        stats,
+    }
+}
+
+/// Index of the tree of each vertex.
+fn tree_of<V: VertexId>(forest: &[Tree<V>], n_verts: usize) -> Vec<usize> {
+    let tree_of = (0..n_verts)
+        .map(|_| AtomicUsize::new(NONE))
+        .collect::<Vec<_>>();
+
+    forest.par_iter().enumerate().for_each(|(i, tree)| {
+        tree_of[tree.root.index()].store(i, Ordering::Relaxed);
+
+        for edge in &tree.edges {
+            tree_of[edge.to.index()].store(i, Ordering::Relaxed);
+        }
+    });
+
+    let tree_of = tree_of
+        .into_par_iter()
+        .map(AtomicUsize::into_inner)
+        .collect::<Vec<_>>();
+
+    assert!(
+        tree_of.par_iter().all(|&t| t != NONE),
+        "the forest doesn't contain every vertex of the graph"
+    );
+
+    tree_of
+}
+
+/// Strongly connected components of the graph of trees, where the
+/// neighbours of `t` are `edges[offsets[t]..offsets[t + 1]]`.
+///
+/// Uses Tarjan's algorithm, which finds a component only after all
+/// components reachable from it, so they are in the order the trees can be
+/// visited. The trees in each component are sorted.
+fn components(offsets: &[usize], edges: &[usize]) -> Vec<Vec<usize>> {
+    let mut tarjan = Tarjan::new(offsets.len() - 1);
+    let mut result = Vec::new();
+
+    // Tree and position of the next edge to follow
+    let mut calls = Vec::new();
+
+    for start in 0..tarjan.index.len() {
+        if tarjan.index[start] != NONE {
+            continue;
+        }
+
+        tarjan.visit(start);
+        calls.push((start, offsets[start]));
+
+        while let Some(&mut (t, ref mut next)) = calls.last_mut() {
+            if *next < offsets[t + 1] {
+                let u = edges[*next];
+                *next += 1;
+
+                if tarjan.index[u] == NONE {
+                    tarjan.visit(u);
+                    calls.push((u, offsets[u]));
+                } else if tarjan.on_stack[u] {
+                    tarjan.low[t] = tarjan.low[t].min(tarjan.index[u]);
+                }
+
+                continue;
+            }
+
+            calls.pop();
+
+            if let Some(&(parent, _)) = calls.last() {
+                tarjan.low[parent] = tarjan.low[parent].min(tarjan.low[t]);
+            }
+
+            if tarjan.low[t] == tarjan.index[t] {
+                result.push(tarjan.pop_component(t));
+            }
+        }
+    }
+
+    result
+}
+
+/// State of Tarjan's algorithm.
+struct Tarjan {
+    index: Vec<usize>,
+    low: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    next_index: usize,
+}
+
+impl Tarjan {
+    fn new(n: usize) -> Self {
+        Tarjan {
+            index: vec![NONE; n],
+            low: vec![0; n],
+            on_stack: vec![false; n],
+            stack: Vec::new(),
+            next_index: 0,
+        }
+    }
+
+    fn visit(&mut self, t: usize) {
+        self.index[t] = self.next_index;
+        self.low[t] = self.next_index;
+        self.next_index += 1;
+        self.on_stack[t] = true;
+        self.stack.push(t);
+    }
+
+    /// Pop the component with root `t` from the stack.
+    fn pop_component(&mut self, t: usize) -> Vec<usize> {
+        let mut component = Vec::new();
+
+        loop {
+            let u = self.stack.pop().unwrap();
+            self.on_stack[u] = false;
+            component.push(u);
+
+            if u == t {
+                break;
+            }
+        }
+
+        component.sort_unstable();
+        component
+    }
+}
+
+/// The forest in the order `repair` puts it, indexed by vertex id.
+struct Order<'r, V> {
+    component: &'r [usize],
+    parents: &'r [Option<V>],
+    pre: &'r [usize],
+    sizes: &'r [usize],
+}
+
+impl<'r, V> Order<'r, V> {
+    /// Whether `u` is in the subtree of `v`, but isn't `v` itself.
+    fn is_below(&self, v: usize, u: usize) -> bool {
+        self.pre[v] < self.pre[u] && self.pre[u] < self.pre[v] + self.sizes[v]
+    }
+}
+
+/// Sequential DFS of component `c`, which has `n_verts` vertices, from each
+/// of `roots`. Returns the trees and the vertices which got a different
+/// parent.
+///
+/// Each vertex goes to its unvisited children of the forest and to any other
+/// unvisited neighbour in the component, except for ones below it in the
+/// forest. Those are found through the tree edges before it is done, unless
+/// one of the vertices on the way is an ancestor in the new tree and so can
+/// only go on after it. Where the forest was already a DFS forest, all other
+/// neighbours were discovered before, so the trees are kept as they are.
+fn regraft<'a, G, R>(
+    graph: G,
+    order: &Order<G::Vertex>,
+    c: usize,
+    n_verts: usize,
+    roots: R,
+    visits: &mut Visits,
+) -> (Vec<Tree<G::Vertex>>, Vec<usize>)
+where
+    G: GraphRef<'a> + Copy,
+    R: Iterator<Item = G::Vertex>,
+{
+    let mut result = Vec::new();
+    let mut regrafted = Vec::new();
+
+    // Both indexed by pre-order number in the component
+    let mut roots = roots.peekable();
+    let first = roots.peek().map_or(0, |root| order.pre[root.index()]);
+    let mut used = vec![false; n_verts];
+    let mut path = BTreeSet::new();
+
+    // Vertex, its remaining neighbours and whether it may skip the vertices
+    // below it
+    let mut calls = Vec::new();
+
+    for root in roots {
+        if used[order.pre[root.index()] - first] {
+            continue;
+        }
+
+        let mut tree = Tree::new(root);
+        let mut next = Some((root, root));
+
+        while let Some((parent, vert)) = next.take() {
+            let v = vert.index();
+            let (start, end) = (order.pre[v] - first, order.pre[v] + order.sizes[v] - first);
+
+            used[start] = true;
+            visits.add();
+
+            if parent != vert {
+                tree.add(Edge::new(parent, vert));
+
+                if order.parents[v] != Some(parent) {
+                    regrafted.push(v);
+                }
+            }
+
+            let skips = path.range(start + 1..end).next().is_none();
+            path.insert(start);
+            calls.push((vert, graph.neighbours(vert), skips));
+
+            while let Some(&mut (vert, ref mut neighbours, skips)) = calls.last_mut() {
+                let v = vert.index();
+
+                let child = neighbours.find(|&u| {
+                    let u = u.index();
+
+                    order.component[u] == c
+                        && !used[order.pre[u] - first]
+                        && !(skips && order.is_below(v, u) && order.parents[u] != Some(vert))
+                });
+
+                if let Some(child) = child {
+                    next = Some((vert, child));
+                    break;
+                }
+
+                path.remove(&(order.pre[v] - first));
+                calls.pop();
+            }
+        }
+
+        result.push(tree);
+    }
+
+    (result, regrafted)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,180 @@
+use rayon::ThreadPoolBuilder;
+
+use crate::dfs::{self, DfsConfig};
+use crate::graph::{AdjLists, Edge, Forest, GraphRef, Tree};
+use crate::progress::{Phase, Progress};
+use super::*;
+
+use std::sync::Mutex;
+
+/// Sink which records the phases and the number of visited vertices.
+#[derive(Default)]
+struct Recorder {
+    phases: Mutex<Vec<Phase>>,
+    visited: AtomicUsize,
+}
+
+impl Progress for Recorder {
+    fn phase(&self, phase: Phase) {
+        self.phases.lock().unwrap().push(phase);
+    }
+
+    fn edges_generated(&self, _: usize) {}
+
+    fn vertices_visited(&self, n: usize) {
+        self.visited.fetch_add(n, Ordering::SeqCst);
+    }
+}
+
+/// Whether `forest` is a DFS forest of `graph` which visits the trees in
+/// order and the children in the order of the neighbours.
+fn is_dfs_forest(graph: &AdjLists, forest: &[Tree]) -> bool {
+    let numbering = forest.numbering(graph);
+    let (pre, post) = (&numbering.pre, &numbering.post);
+
+    let spans = pre.len() == graph.n_verts() && pre.iter().all(|number| number.is_some());
+    let tree_edges = forest
+        .iter()
+        .flat_map(|tree| &tree.edges)
+        .all(|edge| graph.has_edge(edge.from, edge.to));
+
+    // No edge may lead to a vertex discovered after its source was done
+    let edges = graph
+        .edges_par()
+        .all(|edge| !(pre[edge.from] < pre[edge.to] && post[edge.from] < post[edge.to]));
+
+    spans && tree_edges && edges
+}
+
+#[test]
+fn keeps_seq() {
+    let graph: AdjLists = AdjLists::gen_directed(1000, 3000, None);
+    let forest = dfs::seq(&graph);
+    let repaired = repair(&graph, forest.clone());
+
+    assert_eq!(repaired.stats, RepairStats::default());
+    assert_eq!(repaired.forest.len(), forest.len());
+
+    for (tree, expected) in repaired.forest.iter().zip(&forest) {
+        assert_eq!(tree.root, expected.root);
+        assert_eq!(tree.edges, expected.edges);
+    }
+}
+
+#[test]
+fn repairs_cheat() {
+    for &undirected in &[false, true] {
+        let graph: AdjLists = match undirected {
+            true => AdjLists::gen_undirected(2000, 3000, None),
+            false => AdjLists::gen_directed(2000, 5000, None),
+        };
+
+        let repaired = repair(&graph, dfs::cheat(&graph));
+        assert!(is_dfs_forest(&graph, &repaired.forest), "undirected: {}", undirected);
+    }
+}
+
+// Only the vertices below the edges which break the DFS property move, so
+// most of a forest from a concurrent traversal stays where it is.
+#[test]
+fn regrafts_few() {
+    let pool = ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+    let config = DfsConfig {
+        pool: Some(&pool),
+        ..DfsConfig::default()
+    };
+
+    let graph: AdjLists = AdjLists::gen_directed(20_000, 30_000, None);
+    let repaired = repair_with(&graph, dfs::cheat_with(&graph, &config), &config);
+
+    assert!(is_dfs_forest(&graph, &repaired.forest));
+    assert!(
+        repaired.stats.retraversed_vertices < graph.n_verts() / 5,
+        "{:?}",
+        repaired.stats
+    );
+}
+
+// Two trees with edges to each other can't be put in an order.
+#[test]
+fn tangled_trees() {
+    let mut graph: AdjLists = AdjLists::new(4);
+    graph.add_edge(Edge::new(0, 1));
+    graph.add_edge(Edge::new(1, 2));
+    graph.add_edge(Edge::new(2, 3));
+    graph.add_edge(Edge::new(3, 0));
+
+    let mut first = Tree::new(0);
+    first.add(Edge::new(0, 1));
+    let mut second = Tree::new(2);
+    second.add(Edge::new(2, 3));
+
+    let repaired = repair(&graph, vec![first, second]);
+
+    assert!(is_dfs_forest(&graph, &repaired.forest));
+    assert_eq!(repaired.forest.len(), 1);
+    assert_eq!(repaired.stats.tangled_trees, 2);
+    assert_eq!(repaired.stats.retraversed_vertices, 1);
+}
+
+// Trees which only need a different order are kept.
+#[test]
+fn reorders_trees() {
+    let mut graph: AdjLists = AdjLists::new(4);
+    graph.add_edge(Edge::new(0, 1));
+    graph.add_edge(Edge::new(1, 2));
+    graph.add_edge(Edge::new(2, 3));
+
+    let mut first = Tree::new(0);
+    first.add(Edge::new(0, 1));
+    let mut second = Tree::new(2);
+    second.add(Edge::new(2, 3));
+
+    let repaired = repair(&graph, vec![first, second]);
+
+    assert!(is_dfs_forest(&graph, &repaired.forest));
+    assert_eq!(repaired.stats.retraversed_trees, 0);
+    assert_eq!(repaired.forest[0].root, 2);
+    assert_eq!(repaired.forest[1].root, 0);
+}
+
+// A tree which went to a vertex through the wrong child.
+#[test]
+fn broken_tree() {
+    let mut graph: AdjLists = AdjLists::new(3);
+    graph.add_edge(Edge::new(0, 1));
+    graph.add_edge(Edge::new(0, 2));
+    graph.add_edge(Edge::new(1, 2));
+
+    let mut tree = Tree::new(0);
+    tree.add(Edge::new(0, 1));
+    tree.add(Edge::new(0, 2));
+
+    let repaired = repair(&graph, vec![tree]);
+
+    assert!(is_dfs_forest(&graph, &repaired.forest));
+    assert_eq!(repaired.stats.violations, 1);
+    assert_eq!(repaired.stats.broken_trees, 1);
+    assert_eq!(repaired.forest[0].edges, vec![Edge::new(0, 1), Edge::new(1, 2)]);
+}
+
+#[test]
+fn progress_sink() {
+    let mut graph: AdjLists = AdjLists::new(3);
+    graph.add_edge(Edge::new(0, 1));
+    graph.add_edge(Edge::new(1, 2));
+    graph.add_edge(Edge::new(2, 0));
+
+    let recorder = Recorder::default();
+    let config = DfsConfig {
+        progress: Some(&recorder),
+        ..DfsConfig::default()
+    };
+
+    let forest = vec![Tree::new(0), Tree::new(1), Tree::new(2)];
+    let repaired = repair_with(&graph, forest, &config);
+
+    assert!(is_dfs_forest(&graph, &repaired.forest));
+    assert_eq!(*recorder.phases.lock().unwrap(), vec![Phase::Repair]);
+    assert_eq!(recorder.visited.load(Ordering::SeqCst), 3);
+}
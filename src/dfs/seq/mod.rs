@@ -1,24 +1,36 @@
 //! Sequential DFS
 
-use crate::graph::{Edge, GraphRef, Tree};
+use crate::dfs::config::DfsConfig;
+use crate::graph::{Edge, GraphRef, Tree, VertexId};
+use crate::progress::{Phase, Visits};
 
 /// Perform a sequential DFS traversal of the graph and build a forest showing
 /// how it was traversed.
-pub fn run<'a, G: GraphRef<'a> + Copy>(graph: G) -> Vec<Tree> {
+pub fn run<'a, G: GraphRef<'a> + Copy>(graph: G) -> Vec<Tree<G::Vertex>> {
+    run_with(graph, &DfsConfig::default())
+}
+
+/// Same as `run`, but with custom options. Only the progress sink is used.
+pub fn run_with<'a, G: GraphRef<'a> + Copy>(graph: G, config: &DfsConfig) -> Vec<Tree<G::Vertex>> {
+    let sink = config.sink();
+    sink.phase(Phase::Traverse);
+
+    let mut visits = Visits::new(sink);
     let mut result = Vec::new();
-    let mut used = vec![false; graph.vertices().count()];
+    let mut used = vec![false; graph.n_verts()];
     let mut stack = Vec::new();
 
     for root in graph.vertices() {
-        if used[root] {
+        if used[root.index()] {
             continue;
         }
 
         let mut tree = Tree::new(root);
-        used[root] = true;
+        used[root.index()] = true;
+        visits.add();
 
         for v in graph.neighbours(root).rev() {
-            if !used[v] {
+            if !used[v.index()] {
                 stack.push((root, v));
             }
         }
@@ -26,12 +38,13 @@ pub fn run<'a, G: GraphRef<'a> + Copy>(graph: G) -> Vec<Tree> {
         while !stack.is_empty() {
             let (parent, vert) = stack.pop().unwrap();
 
-            if !used[vert] {
-                used[vert] = true;
+            if !used[vert.index()] {
+                used[vert.index()] = true;
                 tree.add(Edge::new(parent, vert));
+                visits.add();
 
                 for child in graph.neighbours(vert).rev() {
-                    if !used[child] {
+                    if !used[child.index()] {
                         stack.push((vert, child));
                     }
                 }
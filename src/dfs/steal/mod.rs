@@ -0,0 +1,237 @@
+//! Parallel DFS with work stealing
+
+use crossbeam_deque::{Deque, Steal, Stealer};
+use rayon::prelude::*;
+
+use crate::dfs::cheat::util::take_ownership;
+use crate::dfs::config::DfsConfig;
+use crate::graph::{Edge, GraphRef, Tree, VertexId};
+use crate::progress::{Phase, Sink, Visits};
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::usize;
+
+/// An entry in the DFS stack of a worker: the root of the tree which reached
+/// the vertex, the parent and the vertex itself.
+type Entry<V> = (V, V, V);
+
+/// Perform a parallel DFS traversal of the graph using work stealing.
+///
+/// Every worker thread runs a sequential DFS on its own stack. When a worker
+/// runs out of work it steals the bottom half of the stack of another worker,
+/// i.e. the entries which are closest to the root and are likely to have the
+/// most work hanging off them. If there is nothing to steal it starts a new
+/// tree from the next vertex which isn't visited yet.
+///
+/// The shared `owner` array works the same way as in `dfs::cheat`. A vertex
+/// belongs to the tree which took ownership of it first, regardless of which
+/// worker did the work.
+pub fn run<'a, G: GraphRef<'a> + Copy + Sync>(graph: G) -> Vec<Tree<G::Vertex>> {
+    run_inner(graph, &DfsConfig::default())
+}
+
+/// Same as `run`, but on the thread pool from `config`. There is one worker
+/// for every thread of the pool.
+pub fn run_with<'a, G: GraphRef<'a> + Copy + Send + Sync>(
+    graph: G,
+    config: &DfsConfig,
+) -> Vec<Tree<G::Vertex>> {
+    config.install(|| run_inner(graph, config))
+}
+
+fn run_inner<'a, G: GraphRef<'a> + Copy + Sync>(
+    graph: G,
+    config: &DfsConfig,
+) -> Vec<Tree<G::Vertex>> {
+    let n_verts = graph.n_verts();
+    let n_workers = rayon::current_num_threads();
+    let sink = config.sink();
+
+    sink.phase(Phase::Traverse);
+
+    let owner = (0..n_verts)
+        .map(|_| AtomicUsize::new(usize::MAX))
+        .collect::<Vec<_>>();
+
+    let workers = (0..n_workers).map(|_| Deque::new()).collect::<Vec<_>>();
+    let stealers = workers.iter().map(Deque::stealer).collect::<Vec<_>>();
+
+    let shared = Shared {
+        owner: &owner,
+        stealers: &stealers,
+        lens: &(0..n_workers).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>(),
+        next_root: &AtomicUsize::new(0),
+        active: &AtomicUsize::new(0),
+    };
+
+    let results = workers
+        .into_par_iter()
+        .enumerate()
+        .map(|(id, worker)| work(graph, id, &worker, &shared, sink))
+        .collect::<Vec<_>>();
+
+    // Roots are claimed in increasing order, so sorting them gives the
+    // order in which they were found, like in the other algorithms
+    let mut roots = results
+        .iter()
+        .flat_map(|(roots, _)| roots.iter().cloned())
+        .collect::<Vec<_>>();
+    roots.sort_unstable();
+
+    // Put together the parts of each tree discovered by different workers
+    let mut trees = roots.iter().map(|&root| Tree::new(root)).collect::<Vec<_>>();
+
+    for (_, edges) in results {
+        for (root, edges) in edges {
+            let i = roots.binary_search(&root).unwrap();
+            trees[i].edges.extend(edges);
+        }
+    }
+
+    trees
+}
+
+/// State shared between all workers.
+struct Shared<'s, V: VertexId> {
+    owner: &'s [AtomicUsize],
+    stealers: &'s [Stealer<Entry<V>>],
+    /// Approximate number of entries in the stack of each worker. Never
+    /// less than the actual number.
+    lens: &'s [AtomicUsize],
+    /// The next vertex to try as a root of a new tree.
+    next_root: &'s AtomicUsize,
+    /// Number of workers which currently have work. When it is 0 all the
+    /// stacks are empty.
+    active: &'s AtomicUsize,
+}
+
+/// Main loop of a single worker.
+///
+/// Returns the roots of the trees started by this worker and the edges it
+/// added to each tree.
+fn work<'a, G: GraphRef<'a> + Copy + Sync>(
+    graph: G,
+    id: usize,
+    stack: &Deque<Entry<G::Vertex>>,
+    shared: &Shared<G::Vertex>,
+    sink: Sink,
+) -> (Vec<G::Vertex>, HashMap<G::Vertex, Vec<Edge<G::Vertex>>>) {
+    let n_verts = shared.owner.len();
+
+    let mut visits = Visits::new(sink);
+    let mut roots = Vec::new();
+    let mut edges = HashMap::new();
+
+    loop {
+        // Announce that we are about to get work before looking for it,
+        // so that others don't give up while stolen entries are in transit.
+        shared.active.fetch_add(1, Ordering::SeqCst);
+
+        if steal(id, stack, shared) {
+            // continue with the stolen entries
+        } else if let Some(root) = next_root(shared) {
+            roots.push(root);
+            visits.add();
+            push_neighbours(graph, id, stack, shared, root, root);
+        } else {
+            shared.active.fetch_sub(1, Ordering::SeqCst);
+
+            if shared.next_root.load(Ordering::SeqCst) >= n_verts
+                && shared.active.load(Ordering::SeqCst) == 0
+            {
+                break;
+            }
+
+            thread::yield_now();
+            continue;
+        }
+
+        while let Some((root, parent, v)) = stack.pop() {
+            shared.lens[id].fetch_sub(1, Ordering::SeqCst);
+
+            if take_ownership(&shared.owner[v.index()], root.index()) {
+                edges
+                    .entry(root)
+                    .or_insert_with(Vec::new)
+                    .push(Edge::new(parent, v));
+                visits.add();
+
+                push_neighbours(graph, id, stack, shared, root, v);
+            }
+        }
+
+        shared.active.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    (roots, edges)
+}
+
+/// Push the neighbours of `v` which aren't owned yet on the stack.
+fn push_neighbours<'a, G: GraphRef<'a> + Copy + Sync>(
+    graph: G,
+    id: usize,
+    stack: &Deque<Entry<G::Vertex>>,
+    shared: &Shared<G::Vertex>,
+    root: G::Vertex,
+    v: G::Vertex,
+) {
+    for child in graph.neighbours(v).rev() {
+        if shared.owner[child.index()].load(Ordering::SeqCst) == usize::MAX {
+            shared.lens[id].fetch_add(1, Ordering::SeqCst);
+            stack.push((root, v, child));
+        }
+    }
+}
+
+/// Take ownership of the next vertex which isn't visited yet, if any.
+fn next_root<V: VertexId>(shared: &Shared<V>) -> Option<V> {
+    loop {
+        let root = shared.next_root.fetch_add(1, Ordering::SeqCst);
+
+        if root >= shared.owner.len() {
+            return None;
+        }
+
+        if take_ownership(&shared.owner[root], root) {
+            return Some(V::from_index(root));
+        }
+    }
+}
+
+/// Try to steal the bottom half of the stack of another worker and push it
+/// on our own stack. Returns `false` if all other stacks are empty.
+fn steal<V: VertexId>(id: usize, stack: &Deque<Entry<V>>, shared: &Shared<V>) -> bool {
+    let n_workers = shared.stealers.len();
+
+    for victim in (1..n_workers).map(|i| (id + i) % n_workers) {
+        let to_steal = (shared.lens[victim].load(Ordering::SeqCst) + 1) / 2;
+        let mut stolen = 0;
+
+        while stolen < to_steal {
+            match shared.stealers[victim].steal() {
+                Steal::Data(entry) => {
+                    shared.lens[victim].fetch_sub(1, Ordering::SeqCst);
+                    shared.lens[id].fetch_add(1, Ordering::SeqCst);
+
+                    // The oldest entries come out first, so they end up
+                    // at the bottom of our stack too
+                    stack.push(entry);
+                    stolen += 1;
+                },
+                Steal::Retry => continue,
+                Steal::Empty => break,
+            }
+        }
+
+        if stolen > 0 {
+            return true;
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,66 @@
+use rayon::ThreadPoolBuilder;
+
+use crate::dfs;
+use crate::graph::{tree, AdjLists, AdjMatrix, GraphRef};
+use super::*;
+
+#[test]
+fn visits_all() {
+    let graph: AdjLists = AdjLists::gen_directed(100, 1000, None);
+    assert!(tree::is_spanning(graph.vertices().count(), &run(&graph)));
+
+    let graph = AdjMatrix::gen_directed(1000, 3000, None);
+    assert!(tree::is_spanning(graph.vertices().count(), &run(&graph)));
+
+    for t in &[2, 4, 8] {
+        ThreadPoolBuilder::new()
+            .num_threads(*t)
+            .build()
+            .unwrap()
+            .install(|| {
+                let graph: AdjLists = AdjLists::gen_undirected(1000, 5000, None);
+                assert!(tree::is_spanning(graph.vertices().count(), &run(&graph)));
+            });
+    }
+}
+
+// The result should be equivalent to the single threaded algorithm
+// when run on 1 thread.
+#[test]
+fn matches_seq() {
+    let thread_pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+
+    thread_pool.install(|| {
+        let graph: AdjLists = AdjLists::gen_directed(100, 1000, None);
+        let mut answer = dfs::seq(&graph);
+        let mut forest = dfs::steal(&graph);
+
+        answer.sort_unstable_by_key(|tree| tree.root);
+        forest.sort_unstable_by_key(|tree| tree.root);
+        assert_eq!(forest.len(), answer.len());
+
+        for (tree, answer) in forest.iter_mut().zip(&mut answer) {
+            tree.edges.sort_unstable_by_key(|edge| edge.from);
+            answer.edges.sort_unstable_by_key(|edge| edge.from);
+
+            assert_eq!(tree.root, answer.root);
+            assert_eq!(tree.edges, answer.edges);
+        }
+    });
+}
+
+// Trees come in the order their roots were found, like in `dfs::seq`.
+#[test]
+fn root_order() {
+    let graph: AdjLists = AdjLists::gen_directed(1000, 1500, None);
+    let forest = ThreadPoolBuilder::new()
+        .num_threads(4)
+        .build()
+        .unwrap()
+        .install(|| run(&graph));
+
+    assert!(forest.windows(2).all(|pair| pair[0].root < pair[1].root));
+}
@@ -0,0 +1,187 @@
+//! `GraphRef` implementations for graphs stored outside of this crate
+//!
+//! They let the algorithms run directly on the data, without copying it into
+//! an `AdjLists` first. Neighbours must be in `0..n_verts`, otherwise the
+//! algorithms panic with an out of bounds index.
+
+use rayon::prelude::*;
+use std::iter;
+use std::marker::PhantomData;
+
+use crate::graph::{Edge, GraphRef, VertexId};
+
+/// `edges_par` of adjacency lists, with function pointers instead of closures
+/// so that the type can be named.
+pub type ListsEdgesPar<'a, V> = rayon::iter::FlatMap<
+    rayon::iter::Enumerate<rayon::slice::Iter<'a, Vec<V>>>,
+    fn((usize, &'a Vec<V>)) -> ListEdges<'a, V>,
+>;
+
+pub(crate) type ListEdges<'a, V> = rayon::iter::Map<
+    rayon::iter::Zip<rayon::iter::RepeatN<V>, rayon::slice::Iter<'a, V>>,
+    fn((V, &'a V)) -> Edge<V>,
+>;
+
+/// Adjacency lists, where `lists[u]` holds the neighbours of `u`. The same
+/// representation as `AdjLists`.
+impl<'a, V: VertexId> GraphRef<'a> for &'a [Vec<V>] {
+    type Vertex = V;
+    type Vertices = iter::Map<std::ops::Range<usize>, fn(usize) -> V>;
+    type VerticesPar = rayon::iter::Map<rayon::range::Iter<usize>, fn(usize) -> V>;
+    type Neighbours = iter::Cloned<std::slice::Iter<'a, V>>;
+    type EdgesPar = ListsEdgesPar<'a, V>;
+
+    fn vertices(self) -> Self::Vertices {
+        (0..self.len()).map(V::from_index as fn(usize) -> V)
+    }
+
+    fn vertices_par(self) -> Self::VerticesPar {
+        (0..self.len()).into_par_iter().map(V::from_index as fn(usize) -> V)
+    }
+
+    fn neighbours(self, v: V) -> Self::Neighbours {
+        self[v.index()].iter().cloned()
+    }
+
+    fn edges_par(self) -> Self::EdgesPar {
+        fn list_edges<'l, V: VertexId>((from, list): (usize, &'l Vec<V>)) -> ListEdges<'l, V> {
+            rayon::iter::repeatn(V::from_index(from), list.len())
+                .zip(list.par_iter())
+                .map(|(from, &to)| Edge::new(from, to))
+        }
+
+        self.par_iter().enumerate().flat_map(list_edges as _)
+    }
+
+    fn n_verts(self) -> usize {
+        self.len()
+    }
+
+    fn out_degree(self, v: V) -> usize {
+        self[v.index()].len()
+    }
+}
+
+/// Same as for `&[Vec<V>]`.
+impl<'a, V: VertexId> GraphRef<'a> for &'a Vec<Vec<V>> {
+    type Vertex = V;
+    type Vertices = <&'a [Vec<V>] as GraphRef<'a>>::Vertices;
+    type VerticesPar = <&'a [Vec<V>] as GraphRef<'a>>::VerticesPar;
+    type Neighbours = <&'a [Vec<V>] as GraphRef<'a>>::Neighbours;
+    type EdgesPar = <&'a [Vec<V>] as GraphRef<'a>>::EdgesPar;
+
+    fn vertices(self) -> Self::Vertices {
+        self.as_slice().vertices()
+    }
+
+    fn vertices_par(self) -> Self::VerticesPar {
+        self.as_slice().vertices_par()
+    }
+
+    fn neighbours(self, v: V) -> Self::Neighbours {
+        self.as_slice().neighbours(v)
+    }
+
+    fn edges_par(self) -> Self::EdgesPar {
+        self.as_slice().edges_par()
+    }
+
+    fn n_verts(self) -> usize {
+        self.len()
+    }
+
+    fn out_degree(self, v: V) -> usize {
+        self[v.index()].len()
+    }
+}
+
+/// Graph given by the number of vertices and a function returning the
+/// neighbours of a vertex.
+///
+/// The function is called every time the neighbours are needed, so it should
+/// be cheap. It is called from many threads at once.
+///
+/// ```ignore
+/// // Ring with `n` vertices
+/// let graph = FnGraph::new(n, |v: usize| iter::once((v + 1) % n));
+/// let forest = dfs::par(&graph);
+/// ```
+pub struct FnGraph<F, V: VertexId = usize> {
+    n_verts: usize,
+    neighbours: F,
+    vertex: PhantomData<fn(V)>,
+}
+
+impl<F, N, V> FnGraph<F, V>
+where
+    F: Fn(V) -> N,
+    N: IntoIterator<Item = V>,
+    V: VertexId,
+{
+    pub fn new(n_verts: usize, neighbours: F) -> Self {
+        FnGraph {
+            n_verts,
+            neighbours,
+            vertex: PhantomData,
+        }
+    }
+}
+
+/// `edges_par` of `FnGraph`.
+type FnEdgesPar<'a, F, V> = rayon::iter::FlatMap<
+    rayon::iter::Zip<rayon::iter::RepeatN<&'a FnGraph<F, V>>, rayon::range::Iter<usize>>,
+    fn((&'a FnGraph<F, V>, usize)) -> Vec<Edge<V>>,
+>;
+
+impl<'a, F, N, V> GraphRef<'a> for &'a FnGraph<F, V>
+where
+    F: Fn(V) -> N + Sync,
+    N: IntoIterator<Item = V>,
+    N::IntoIter: DoubleEndedIterator + 'a,
+    V: VertexId,
+{
+    type Vertex = V;
+    type Vertices = iter::Map<std::ops::Range<usize>, fn(usize) -> V>;
+    type VerticesPar = rayon::iter::Map<rayon::range::Iter<usize>, fn(usize) -> V>;
+    type Neighbours = N::IntoIter;
+    type EdgesPar = FnEdgesPar<'a, F, V>;
+
+    fn vertices(self) -> Self::Vertices {
+        (0..self.n_verts).map(V::from_index as fn(usize) -> V)
+    }
+
+    fn vertices_par(self) -> Self::VerticesPar {
+        (0..self.n_verts).into_par_iter().map(V::from_index as fn(usize) -> V)
+    }
+
+    fn neighbours(self, v: V) -> Self::Neighbours {
+        (self.neighbours)(v).into_iter()
+    }
+
+    fn edges_par(self) -> Self::EdgesPar {
+        fn vertex_edges<'g, F, N, V>((graph, from): (&'g FnGraph<F, V>, usize)) -> Vec<Edge<V>>
+        where
+            F: Fn(V) -> N,
+            N: IntoIterator<Item = V>,
+            V: VertexId,
+        {
+            let from = V::from_index(from);
+
+            (graph.neighbours)(from)
+                .into_iter()
+                .map(|to| Edge::new(from, to))
+                .collect()
+        }
+
+        rayon::iter::repeatn(self, self.n_verts)
+            .zip(0..self.n_verts)
+            .flat_map(vertex_edges as _)
+    }
+
+    fn n_verts(self) -> usize {
+        self.n_verts
+    }
+}
+
+#[cfg(test)]
+mod tests;
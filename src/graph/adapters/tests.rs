@@ -0,0 +1,92 @@
+use rayon::ThreadPoolBuilder;
+
+use crate::dfs;
+use crate::graph::{AdjLists, Tree};
+use super::*;
+
+fn sorted(mut forest: Vec<Tree>) -> Vec<Tree> {
+    forest.sort_unstable_by_key(|tree| tree.root);
+
+    for tree in &mut forest {
+        tree.edges.sort_unstable_by_key(|edge| (edge.from, edge.to));
+    }
+
+    forest
+}
+
+fn assert_same(forest: Vec<Tree>, answer: Vec<Tree>) {
+    let forest = sorted(forest);
+    let answer = sorted(answer);
+
+    assert_eq!(forest.len(), answer.len());
+
+    for (tree, answer) in forest.iter().zip(&answer) {
+        assert_eq!(tree.root, answer.root);
+        assert_eq!(tree.edges, answer.edges);
+    }
+}
+
+// All algorithms should give the same result as on an `AdjLists` with the
+// same edges when run on 1 thread.
+#[test]
+fn matches_adj_lists() {
+    let graph: AdjLists = AdjLists::gen_directed(100, 1000, None);
+
+    let lists = graph
+        .vertices()
+        .map(|v| graph.neighbours(v).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    let fn_graph = FnGraph::new(lists.len(), |v: usize| lists[v].iter().cloned());
+
+    let thread_pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+
+    thread_pool.install(|| {
+        assert_same(dfs::seq(&lists), dfs::seq(&graph));
+        assert_same(dfs::seq(lists.as_slice()), dfs::seq(&graph));
+        assert_same(dfs::seq(&fn_graph), dfs::seq(&graph));
+
+        assert_same(dfs::par(&lists), dfs::par(&graph));
+        assert_same(dfs::par(lists.as_slice()), dfs::par(&graph));
+        assert_same(dfs::par(&fn_graph), dfs::par(&graph));
+
+        assert_same(dfs::cheat(&lists), dfs::cheat(&graph));
+        assert_same(dfs::cheat(lists.as_slice()), dfs::cheat(&graph));
+        assert_same(dfs::cheat(&fn_graph), dfs::cheat(&graph));
+    });
+}
+
+#[test]
+fn queries() {
+    let lists = vec![vec![1, 2], vec![2], vec![]];
+    let fn_graph = FnGraph::new(3, |v: usize| lists[v].clone());
+
+    assert_eq!(lists.n_verts(), 3);
+    assert_eq!(lists.n_edges(), 3);
+    assert_eq!(lists.out_degree(0), 2);
+    assert!(lists.has_edge(1, 2));
+    assert!(!lists.has_edge(2, 1));
+
+    assert_eq!(fn_graph.n_verts(), 3);
+    assert_eq!(fn_graph.n_edges(), 3);
+    assert_eq!(fn_graph.out_degree(0), 2);
+    assert!(fn_graph.has_edge(1, 2));
+    assert!(!fn_graph.has_edge(2, 1));
+
+    let mut edges = fn_graph.edges_par().collect::<Vec<_>>();
+    edges.sort_unstable_by_key(|edge| (edge.from, edge.to));
+    assert_eq!(edges, lists.edges_par().collect::<Vec<_>>());
+}
+
+#[test]
+fn ring() {
+    let n = 1000;
+    let graph = FnGraph::new(n, |v: usize| iter::once((v + 1) % n));
+    let forest = dfs::par(&graph);
+
+    assert_eq!(forest.len(), 1);
+    assert_eq!(forest[0].edges.len(), n - 1);
+}
@@ -4,17 +4,18 @@ use rayon::prelude::*;
 use std::mem;
 use std::ops::Range;
 
-use super::Prng;
+use super::{Prng, VertexId};
+use crate::progress::{self, EDGES_PER_REPORT};
 
-pub struct Job<'a> {
+pub struct Job<'a, V: VertexId> {
     from_verts: Range<usize>,
     to_verts: Range<usize>,
     n_edges: usize,
-    lists: &'a mut [Vec<usize>],
+    lists: &'a mut [Vec<V>],
     directed: bool,
 }
 
-impl<'a> Job<'a> {
+impl<'a, V: VertexId> Job<'a, V> {
     pub fn gen(&mut self, seed: Option<<Prng as SeedableRng>::Seed>) {
         let mut added = 0;
         let mut rng = match seed {
@@ -29,36 +30,56 @@ impl<'a> Job<'a> {
             let from = from_range.sample(&mut rng);
             let to = to_range.sample(&mut rng);
 
+            // The graph was created with `n_verts` which fit in `V`
             if self.should_add(from, to) {
-                self.list_at(from).push(to);
+                self.list_at(from).push(V::from_index_unchecked(to));
                 added += 1;
+
+                if added % EDGES_PER_REPORT == 0 {
+                    progress::edges_generated(EDGES_PER_REPORT);
+                }
             }
         }
+
+        progress::edges_generated(added % EDGES_PER_REPORT);
     }
 
     fn should_add(&mut self, from: usize, to: usize) -> bool {
         if self.directed {
-            from != to && self.list_at(from).iter().find(|&&e| e == to).is_none()
+            from != to && self.list_at(from).iter().find(|&&e| e.index() == to).is_none()
         } else {
-            from > to && self.list_at(from).iter().find(|&&e| e == to).is_none()
+            from > to && self.list_at(from).iter().find(|&&e| e.index() == to).is_none()
         }
     }
 
-    fn list_at(&mut self, v: usize) -> &mut Vec<usize> {
+    fn list_at(&mut self, v: usize) -> &mut Vec<V> {
         &mut self.lists[v - self.from_verts.start]
     }
 }
 
 #[derive(Debug)]
-pub struct JobDesc<'a> {
+pub struct JobDesc<'a, V: VertexId> {
     pub n_verts: usize,
     pub n_edges: usize,
-    pub lists: &'a mut [Vec<usize>],
+    pub lists: &'a mut [Vec<V>],
     pub directed: bool,
 }
 
-impl<'a> JobDesc<'a> {
-    pub fn chunked(self, verts_per_chunk: usize) -> impl IndexedParallelIterator<Item = Job<'a>> + 'a{
+impl<'a, V: VertexId> JobDesc<'a, V> {
+    pub fn chunked(self, verts_per_chunk: usize) -> impl IndexedParallelIterator<Item = Job<'a, V>> + 'a{
+        self.chunked_from(0, verts_per_chunk)
+    }
+
+    /// Same as `chunked`, but `lists` are only the lists of the vertices from
+    /// `first_vert` on, which must be the start of a chunk. The jobs are the
+    /// same as the ones `chunked` gives for these vertices.
+    pub fn chunked_from(
+        self,
+        first_vert: usize,
+        verts_per_chunk: usize,
+    ) -> impl IndexedParallelIterator<Item = Job<'a, V>> + 'a {
+        debug_assert!(first_vert % verts_per_chunk == 0);
+
         let directed = self.directed;
         let n_edges = self.n_edges;
         let n_verts = self.n_verts;
@@ -67,7 +88,7 @@ impl<'a> JobDesc<'a> {
             .par_chunks_mut(verts_per_chunk)
             .enumerate()
             .map(move |(i, lists)| {
-                let start = i * verts_per_chunk;
+                let start = first_vert + i * verts_per_chunk;
                 let end = start + lists.len();
 
                 if directed {
@@ -98,7 +119,7 @@ impl<'a> JobDesc<'a> {
             })
     }
 
-    pub fn threaded(self, n_threads: usize) -> impl Iterator<Item = Job<'a>> {
+    pub fn threaded(self, n_threads: usize) -> impl Iterator<Item = Job<'a, V>> {
         let n_verts = self.n_verts;
         let n_edges = self.n_edges;
         let mut state = self.lists;
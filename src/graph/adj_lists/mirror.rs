@@ -4,21 +4,23 @@ use spin::Mutex as SpinLock;
 
 use std::sync::Mutex;
 
-pub fn seq(lists: &mut Vec<Vec<usize>>) {
+use crate::graph::VertexId;
+
+pub fn seq<V: VertexId>(lists: &mut Vec<Vec<V>>) {
     for u in 0..lists.len() {
         let (before, after) = lists.split_at_mut(u);
         let (list, _) = after.split_first_mut().unwrap();
 
         for &v in list.iter() {
-            match v {
-                _ if v < u => before[v].push(u),
+            match v.index() {
+                v if v < u => before[v].push(V::from_index(u)),
                 _ => (),
             }
         }
     }
 }
 
-pub fn mutex(lists: &mut Vec<Vec<usize>>) {
+pub fn mutex<V: VertexId>(lists: &mut Vec<Vec<V>>) {
     let locked = lists.iter_mut().map(|list| Mutex::new(list)).collect::<Vec<_>>();
 
     locked
@@ -27,14 +29,14 @@ pub fn mutex(lists: &mut Vec<Vec<usize>>) {
         .for_each(|(u, list)| {
             // can't deadlock because v < u
             for &v in list.lock().unwrap().iter() {
-                if v < u {
-                    locked[v].lock().unwrap().push(u);
+                if v.index() < u {
+                    locked[v.index()].lock().unwrap().push(V::from_index(u));
                 }
             }
         })
 }
 
-pub fn spin_lock(lists: &mut Vec<Vec<usize>>) {
+pub fn spin_lock<V: VertexId>(lists: &mut Vec<Vec<V>>) {
     let locked = lists.iter_mut().map(|list| SpinLock::new(list)).collect::<Vec<_>>();
 
     locked
@@ -43,14 +45,14 @@ pub fn spin_lock(lists: &mut Vec<Vec<usize>>) {
         .for_each(|(u, list)| {
             // can't deadlock because v < u
             for &v in list.lock().iter() {
-                if v < u {
-                    locked[v].lock().push(u);
+                if v.index() < u {
+                    locked[v.index()].lock().push(V::from_index(u));
                 }
             }
         })
 }
 
-pub fn queue(lists: &mut Vec<Vec<usize>>) {
+pub fn queue<V: VertexId>(lists: &mut Vec<Vec<V>>) {
     let queues = lists.iter().map(|_| SegQueue::new()).collect::<Vec<_>>();
 
     lists
@@ -58,7 +60,7 @@ pub fn queue(lists: &mut Vec<Vec<usize>>) {
         .enumerate()
         .for_each(|(u, list)| {
             for &v in list.iter() {
-                queues[v].push(u);
+                queues[v.index()].push(V::from_index(u));
             }
         });
 
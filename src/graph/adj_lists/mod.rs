@@ -1,14 +1,23 @@
 use rand::prelude::*;
-use rayon::{self, prelude::*};
+use rayon::{self, prelude::*, ThreadPool};
+use std::io::{self, Write};
 use std::iter;
 
-use crate::graph::{Edge, GraphRef, Prng};
+use crate::dfs::{Algorithm, DfsConfig};
+use crate::graph::{Edge, Graph, GraphRef, Prng, Tree, VertexId};
+use crate::progress::{self, Phase};
 
 mod job;
 pub mod mirror;
+pub mod stream;
 
 use self::job::JobDesc;
 
+// Number of vertices bellow which we prefer to calculate sequentially
+// TODO: benchmark to choose an appropriate value
+// TODO: should we parallelize over number of edges instead?
+const VERTS_PER_CHUNK: usize = 128;
+
 /// Simple graph represented using adjacency lists.
 ///
 /// Vertices are represented with integer ids in `0..n_verts`.
@@ -29,21 +38,106 @@ use self::job::JobDesc;
 /// The graph is directed. An undirected graph is represented
 /// by adding an edge in both directions (ie *(u, v)* and *(v, u)*).
 /// Loops (*(u, u)* edges) and multiple edges are not allowed.
+///
+/// Vertex ids are stored as `V`. Using `u32` halves the memory of the lists
+/// on 64-bit platforms, as long as there are less than 2^32 vertices.
+///
+/// `has_edge` uses binary search. If the lists aren't sorted, e.g. after
+/// generating the graph, the first call makes a sorted copy of them, which
+/// takes as much memory as the lists. Call `sort` to avoid it, if the order
+/// of the neighbours doesn't matter.
 #[derive(Debug, Clone)]
-pub struct AdjLists {
+pub struct AdjLists<V: VertexId = usize> {
     n_verts: usize,
-    lists: Vec<Vec<usize>>,
+    n_edges: usize,
+    /// Whether every list is sorted, so that `has_edge` can use them directly.
+    sorted: bool,
+    lists: Vec<Vec<V>>,
+    lookup: Lookup<V>,
+}
+
+/// Sorted copy of the lists of an `AdjLists`, made on first use.
+#[derive(Debug)]
+struct Lookup<V>(spin::Once<Vec<Vec<V>>>);
+
+impl<V> Default for Lookup<V> {
+    fn default() -> Self {
+        Lookup(spin::Once::new())
+    }
+}
+
+impl<V> Clone for Lookup<V> {
+    /// The copy is made again when it is needed.
+    fn clone(&self) -> Self {
+        Lookup(spin::Once::new())
+    }
 }
 
-impl AdjLists {
+impl<V: VertexId> AdjLists<V> {
     /// Create new empty graph
+    ///
+    /// # Panics
+    ///
+    /// If some of the vertices can't be represented by `V`.
     pub fn new(n_verts: usize) -> Self {
+        assert!(n_verts == 0 || n_verts - 1 <= V::MAX, "too many vertices");
+
         AdjLists {
             n_verts,
+            n_edges: 0,
+            sorted: true,
             lists: vec![vec![]; n_verts],
+            lookup: Lookup::default(),
         }
     }
 
+    /// Create a graph from lists which are already filled in, e.g. read from
+    /// a file. They must not contain loops or multiple edges, which is not
+    /// checked.
+    pub(crate) fn from_lists(lists: Vec<Vec<V>>) -> Self {
+        assert!(lists.is_empty() || lists.len() - 1 <= V::MAX, "too many vertices");
+
+        let sorted = lists
+            .par_iter()
+            .all(|list| list.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let mut graph = AdjLists {
+            n_verts: lists.len(),
+            n_edges: 0,
+            sorted: false,
+            lists,
+            lookup: Lookup::default(),
+        };
+
+        graph.generated();
+        graph.sorted = sorted;
+        graph
+    }
+
+    /// Add the edge *(from, to)* to the graph.
+    ///
+    /// # Panics
+    ///
+    /// If the edge is a loop or already exists.
+    pub fn add_edge(&mut self, edge: Edge<V>) {
+        assert!(edge.from != edge.to, "loops are not allowed");
+
+        // Only this list is searched, `has_edge` would sort a copy of all of
+        // them on unsorted lists
+        let list = &mut self.lists[edge.from.index()];
+        let exists = match self.sorted {
+            true => list.binary_search(&edge.to).is_ok(),
+            false => list.contains(&edge.to),
+        };
+        assert!(!exists, "multiple edges are not allowed");
+
+        self.sorted &= list.last().map_or(true, |&last| last < edge.to);
+        self.n_edges += 1;
+        self.lookup = Lookup::default();
+
+        list.push(edge.to);
+    }
+
     /// Create new directed graph with randomly generated edges.
     ///
     /// Creates a graph with `n_verts` vertices and `n_edges` randomly generated
@@ -63,14 +157,10 @@ impl AdjLists {
         I: IntoIterator<Item = <Prng as SeedableRng>::Seed>,
     {
         assert!(n_edges <= n_verts * (n_verts - 1));
+        progress::phase(Phase::Generate);
 
         let mut graph = AdjLists::new(n_verts);
 
-        // Number of vertices bellow which we prefer to calculate sequentially
-        // TODO: benchmark to choose an appropriate value
-        // TODO: should we parallelize over number of edges instead?
-        const VERTS_PER_CHUNK: usize = 128;
-
         // Calculate the number of seeds that we will need and pre-collect them in a
         // vector. We need this because we can't share a mutable iterator
         // between threads without locking.
@@ -91,6 +181,7 @@ impl AdjLists {
         .zip(seeds)
         .for_each(|(mut job, seed)| job.gen(seed));
 
+        graph.generated();
         graph
     }
 
@@ -116,6 +207,7 @@ impl AdjLists {
         <I as IntoIterator>::IntoIter: Send,
     {
         assert!(n_edges <= n_verts * (n_verts - 1));
+        progress::phase(Phase::Generate);
 
         let mut graph = AdjLists::new(n_verts);
         let seeds = seeds.into_iter().map(|s| Some(s)).chain(iter::repeat(None));
@@ -132,6 +224,7 @@ impl AdjLists {
             .for_each(|(mut job, seed)| scope.spawn(move |_| job.gen(seed)));
         });
 
+        graph.generated();
         graph
     }
 
@@ -156,8 +249,7 @@ impl AdjLists {
         I: IntoIterator<Item = <Prng as SeedableRng>::Seed>,
     {
         assert!(n_edges <= n_verts * (n_verts - 1) / 2);
-
-        const VERTS_PER_CHUNK: usize = 128;
+        progress::phase(Phase::Generate);
 
         let mut graph = AdjLists::new(n_verts);
 
@@ -178,11 +270,31 @@ impl AdjLists {
         .zip(seeds)
         .for_each(|(mut job, seed)| job.gen(seed));
 
+        progress::phase(Phase::Mirror);
         mirror::seq(&mut graph.lists);
 
+        graph.generated();
         graph
     }
 
+    /// Same as `gen_directed`, but generates the graph on `pool` instead of
+    /// the current thread pool.
+    pub fn gen_directed_in<I>(pool: &ThreadPool, n_verts: usize, n_edges: usize, seeds: I) -> Self
+    where
+        I: IntoIterator<Item = <Prng as SeedableRng>::Seed> + Send,
+    {
+        pool.install(|| Self::gen_directed(n_verts, n_edges, seeds))
+    }
+
+    /// Same as `gen_undirected`, but generates the graph on `pool` instead of
+    /// the current thread pool.
+    pub fn gen_undirected_in<I>(pool: &ThreadPool, n_verts: usize, n_edges: usize, seeds: I) -> Self
+    where
+        I: IntoIterator<Item = <Prng as SeedableRng>::Seed> + Send,
+    {
+        pool.install(|| Self::gen_undirected(n_verts, n_edges, seeds))
+    }
+
     /// Sort the graph, so that edges come in order for `edges` and
     /// `neighbours`.
     ///
@@ -190,38 +302,112 @@ impl AdjLists {
     pub fn sort(&mut self) {
         self.lists
             .par_iter_mut()
-            .for_each(|list| list.sort_unstable())
+            .for_each(|list| list.sort_unstable());
+
+        self.sorted = true;
+        self.lookup = Lookup::default();
+    }
+
+    /// Update the cached properties after the lists were filled in by
+    /// a generator.
+    fn generated(&mut self) {
+        self.n_edges = self.lists.par_iter().map(|list| list.len()).sum();
+        self.sorted = false;
+        self.lookup = Lookup::default();
+    }
+
+    /// The lists if they are sorted, otherwise a sorted copy of them.
+    fn sorted_lists(&self) -> &[Vec<V>] {
+        if self.sorted {
+            return &self.lists;
+        }
+
+        self.lookup.0.call_once(|| {
+            self.lists
+                .par_iter()
+                .map(|list| {
+                    let mut list = list.clone();
+                    list.sort_unstable();
+                    list
+                })
+                .collect()
+        })
     }
 
     /// Iterator over all edges in the graph.
-    pub fn edges<'a>(&'a self) -> impl Iterator<Item = Edge> + 'a {
+    pub fn edges<'a>(&'a self) -> impl Iterator<Item = Edge<V>> + 'a {
         self.vertices()
             .flat_map(move |v| iter::repeat(v).zip(self.neighbours(v)))
             .map(|(from, to)| Edge::new(from, to))
     }
 }
 
-impl<'a> GraphRef<'a> for &'a AdjLists {
-    type Vertices = std::ops::Range<usize>;
-    type VerticesPar = rayon::range::Iter<usize>;
-    type Neighbours = std::iter::Cloned<std::slice::Iter<'a, usize>>;
+impl<'a, V: VertexId> GraphRef<'a> for &'a AdjLists<V> {
+    type Vertex = V;
+    type Vertices = <&'a [Vec<V>] as GraphRef<'a>>::Vertices;
+    type VerticesPar = <&'a [Vec<V>] as GraphRef<'a>>::VerticesPar;
+    type Neighbours = <&'a [Vec<V>] as GraphRef<'a>>::Neighbours;
+    type EdgesPar = <&'a [Vec<V>] as GraphRef<'a>>::EdgesPar;
 
     /// Iterator over all vertices in the graph.
     fn vertices(self) -> Self::Vertices {
-        0..self.n_verts
+        self.lists.as_slice().vertices()
     }
 
     /// Rayon parallel iterator over all vertices in the graph.
     fn vertices_par(self) -> Self::VerticesPar {
-        (0..self.n_verts).into_par_iter()
+        self.lists.as_slice().vertices_par()
     }
 
     /// Iterator over the neighbours of vertex `v`.
     ///
     /// The neighbours are all vertices `u` such that an edge from `v` to `u`
     /// exists.
-    fn neighbours(self, v: usize) -> Self::Neighbours {
-        self.lists[v].iter().cloned()
+    fn neighbours(self, v: V) -> Self::Neighbours {
+        self.lists.as_slice().neighbours(v)
+    }
+
+    fn edges_par(self) -> Self::EdgesPar {
+        self.lists.as_slice().edges_par()
+    }
+
+    fn n_verts(self) -> usize {
+        self.n_verts
+    }
+
+    fn n_edges(self) -> usize {
+        self.n_edges
+    }
+
+    fn out_degree(self, v: V) -> usize {
+        self.lists[v.index()].len()
+    }
+
+    /// Whether the edge *(from, to)* exists in the graph.
+    ///
+    /// Uses binary search, on a sorted copy of the lists if they aren't
+    /// sorted, see `sort`.
+    fn has_edge(self, from: V, to: V) -> bool {
+        self.sorted_lists()[from.index()].binary_search(&to).is_ok()
+    }
+}
+
+impl<V: VertexId> Graph<V> for AdjLists<V> {
+    fn traverse(&self, algorithm: Algorithm, config: &DfsConfig) -> Vec<Tree<V>> {
+        algorithm.run_ref(self, config)
+    }
+
+    fn write_dot(
+        &self,
+        forest: Option<&[Tree<V>]>,
+        numbers: bool,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        crate::io::write_graph_dot(self, forest, numbers, out)
+    }
+
+    fn write_binary(&self, out: &mut dyn Write) -> io::Result<()> {
+        crate::io::write_binary(self, out)
     }
 }
 
@@ -0,0 +1,138 @@
+//! Generating graphs straight to a file
+//!
+//! Graphs larger than memory can't be generated with `AdjLists::gen_directed`
+//! and then written. These generators run the same jobs a batch of chunks at
+//! a time and write the edges of each batch before generating the next one,
+//! so only the lists of one batch are in memory.
+//!
+//! With the same seeds the file has exactly the graph the in-memory
+//! generators give, with the neighbours of each vertex in the same order.
+//!
+//! ```ignore
+//! let out = BufWriter::new(File::create("big.txt")?);
+//! stream::gen_directed(n_verts, n_edges, graph::seeds(42), Format::Snap, out)?;
+//! ```
+
+use rand::prelude::*;
+use rayon::prelude::*;
+use std::io::{self, Write};
+use std::iter;
+
+use super::{JobDesc, VERTS_PER_CHUNK};
+use crate::graph::Prng;
+use crate::io::{EdgeWriter, Format};
+use crate::progress::{self, Phase};
+
+/// Number of chunks generated in parallel before their edges are written.
+#[cfg(not(test))]
+const CHUNKS_PER_BATCH: usize = 1024;
+#[cfg(test)]
+const CHUNKS_PER_BATCH: usize = 2;
+
+/// Write the graph `AdjLists::gen_directed` gives for `seeds` to `out`.
+///
+/// Fails with `io::ErrorKind::InvalidInput` for formats which can't be
+/// written one edge at a time, see `EdgeWriter`.
+///
+/// # Panics
+///
+/// If `n_edges` is more than the edges of a full graph with `n_verts`
+/// vertices, i.e. (`n_verts  * (n_verts - 1)`)
+pub fn gen_directed<I, W>(
+    n_verts: usize,
+    n_edges: usize,
+    seeds: I,
+    format: Format,
+    out: W,
+) -> io::Result<()>
+where
+    I: IntoIterator<Item = <Prng as SeedableRng>::Seed>,
+    W: Write,
+{
+    assert!(n_edges <= n_verts * n_verts.saturating_sub(1));
+
+    let writer = EdgeWriter::new(format, out, n_verts, n_edges)?;
+    generate(n_verts, n_edges, true, seeds, writer)
+}
+
+/// Write the graph `AdjLists::gen_undirected` gives for `seeds` to `out`.
+///
+/// Every edge is written in both directions, so the file has `2 * n_edges`
+/// edges.
+///
+/// # Panics
+///
+/// If `2 * n_edges` is more than the edges of a full graph with `n_verts`
+/// vertices, i.e. (`n_verts  * (n_verts - 1)`)
+pub fn gen_undirected<I, W>(
+    n_verts: usize,
+    n_edges: usize,
+    seeds: I,
+    format: Format,
+    out: W,
+) -> io::Result<()>
+where
+    I: IntoIterator<Item = <Prng as SeedableRng>::Seed>,
+    W: Write,
+{
+    assert!(n_edges <= n_verts * n_verts.saturating_sub(1) / 2);
+
+    let writer = EdgeWriter::new(format, out, n_verts, 2 * n_edges)?;
+    generate(n_verts, n_edges, false, seeds, writer)
+}
+
+fn generate<I, W>(
+    n_verts: usize,
+    n_edges: usize,
+    directed: bool,
+    seeds: I,
+    mut writer: EdgeWriter<W>,
+) -> io::Result<()>
+where
+    I: IntoIterator<Item = <Prng as SeedableRng>::Seed>,
+    W: Write,
+{
+    progress::phase(Phase::Generate);
+
+    let mut seeds = seeds.into_iter().map(Some).chain(iter::repeat(None));
+    let verts_per_batch = VERTS_PER_CHUNK * CHUNKS_PER_BATCH;
+
+    for start in (0..n_verts).step_by(verts_per_batch) {
+        let mut lists: Vec<Vec<usize>> = vec![vec![]; verts_per_batch.min(n_verts - start)];
+
+        // Chunk `i` of the whole graph gets seed `i`, like in `gen_directed`
+        let batch_seeds = seeds
+            .by_ref()
+            .take(lists.chunks(VERTS_PER_CHUNK).count())
+            .collect::<Vec<_>>();
+
+        JobDesc {
+            n_verts,
+            n_edges,
+            lists: &mut lists,
+            directed,
+        }
+        .chunked_from(start, VERTS_PER_CHUNK)
+        .zip(batch_seeds)
+        .for_each(|(mut job, seed)| job.gen(seed));
+
+        for (from, list) in (start..).zip(&lists) {
+            for &to in list {
+                writer.edge(from, to)?;
+            }
+        }
+
+        // The reverse edges, in the order `mirror::seq` adds them. Each one
+        // starts at a smaller vertex, whose generated edges were written in
+        // this or an earlier batch, so it goes after them like in memory.
+        if !directed {
+            for (from, list) in (start..).zip(&lists) {
+                for &to in list {
+                    writer.edge(to, from)?;
+                }
+            }
+        }
+    }
+
+    writer.finish().map(|_| ())
+}
@@ -1,5 +1,7 @@
 use rayon::ThreadPoolBuilder;
 use super::*;
+use crate::graph;
+use crate::io::Format;
 
 #[test]
 fn generate_directed() {
@@ -8,27 +10,27 @@ fn generate_directed() {
         .build()
         .unwrap()
         .install(|| {
-            let graph = AdjLists::gen_directed(30, 100, None);
+            let graph: AdjLists = AdjLists::gen_directed(30, 100, None);
             assert_eq!(graph.vertices().count(), 30);
             assert_eq!(graph.edges().count(), 100);
         });
 
-    let graph = AdjLists::gen_directed(30, 100, None);
+    let graph: AdjLists = AdjLists::gen_directed(30, 100, None);
     assert_eq!(graph.vertices().count(), 30);
     assert_eq!(graph.edges().count(), 100);
 
-    let graph = AdjLists::gen_directed(300, 10000, None);
+    let graph: AdjLists = AdjLists::gen_directed(300, 10000, None);
     assert_eq!(graph.vertices().count(), 300);
     assert_eq!(graph.edges().count(), 10000);
 }
 
 #[test]
 fn generate_directed_threads() {
-    let graph = AdjLists::gen_directed_on_threads(30, 100, 1, None);
+    let graph: AdjLists = AdjLists::gen_directed_on_threads(30, 100, 1, None);
     assert_eq!(graph.vertices().count(), 30);
     assert_eq!(graph.edges().count(), 100);
 
-    let graph = AdjLists::gen_directed_on_threads(30, 100, 4, None);
+    let graph: AdjLists = AdjLists::gen_directed_on_threads(30, 100, 4, None);
     assert_eq!(graph.vertices().count(), 30);
     assert_eq!(graph.edges().count(), 100);
 }
@@ -40,16 +42,178 @@ fn generate_undirected() {
         .build()
         .unwrap()
         .install(|| {
-            let graph = AdjLists::gen_undirected(30, 100, None);
+            let graph: AdjLists = AdjLists::gen_undirected(30, 100, None);
             assert_eq!(graph.vertices().count(), 30);
             assert_eq!(graph.edges().count(), 200);
         });
 
-    let graph = AdjLists::gen_undirected(30, 100, None);
+    let graph: AdjLists = AdjLists::gen_undirected(30, 100, None);
     assert_eq!(graph.vertices().count(), 30);
     assert_eq!(graph.edges().count(), 200);
 
-    let graph = AdjLists::gen_undirected(300, 10000, None);
+    let graph: AdjLists = AdjLists::gen_undirected(300, 10000, None);
     assert_eq!(graph.vertices().count(), 300);
     assert_eq!(graph.edges().count(), 20000);
 }
+
+#[test]
+fn add_edge() {
+    let mut graph: AdjLists = AdjLists::new(3);
+    graph.add_edge(Edge::new(0, 1));
+    graph.add_edge(Edge::new(0, 2));
+    graph.add_edge(Edge::new(1, 2));
+
+    assert_eq!(graph.edges().collect::<Vec<_>>(), vec![
+        Edge::new(0, 1),
+        Edge::new(0, 2),
+        Edge::new(1, 2),
+    ]);
+}
+
+#[test]
+#[should_panic]
+fn add_edge_twice() {
+    let mut graph: AdjLists = AdjLists::new(3);
+    graph.add_edge(Edge::new(0, 1));
+    graph.add_edge(Edge::new(0, 1));
+}
+
+#[test]
+#[should_panic]
+fn add_edge_twice_unsorted() {
+    let mut graph: AdjLists = AdjLists::new(3);
+    graph.add_edge(Edge::new(0, 2));
+    graph.add_edge(Edge::new(0, 1));
+    graph.add_edge(Edge::new(0, 2));
+}
+
+#[test]
+fn narrow_vertex_id() {
+    let seeds = (0..10u8).map(|i| [i; 16]).collect::<Vec<_>>();
+
+    let wide: AdjLists<usize> = AdjLists::gen_undirected(1000, 5000, seeds.clone());
+    let narrow: AdjLists<u32> = AdjLists::gen_undirected(1000, 5000, seeds);
+
+    assert!(wide
+        .edges()
+        .zip(narrow.edges())
+        .all(|(w, n)| w.from == n.from as usize && w.to == n.to as usize));
+    assert_eq!(wide.edges().count(), narrow.edges().count());
+}
+
+#[test]
+#[should_panic]
+fn vertex_out_of_range() {
+    u32::from_index(u32::max_value() as usize + 1);
+}
+
+#[test]
+fn generate_in_pool() {
+    let pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+    let seeds = (0..10u8).map(|i| [i; 16]).collect::<Vec<_>>();
+
+    let graph: AdjLists = AdjLists::gen_directed_in(&pool, 1000, 5000, seeds.clone());
+    let answer: AdjLists = AdjLists::gen_directed(1000, 5000, seeds);
+
+    assert_eq!(graph.edges().collect::<Vec<_>>(), answer.edges().collect::<Vec<_>>());
+}
+
+#[test]
+fn queries() {
+    let mut graph: AdjLists = AdjLists::gen_undirected(100, 500, None);
+
+    for &sorted in &[false, true] {
+        if sorted {
+            graph.sort();
+        }
+
+        assert_eq!(graph.n_verts(), 100);
+        assert_eq!(graph.n_edges(), 1000);
+
+        let mut edges = graph.edges().collect::<Vec<_>>();
+        let mut edges_par = graph.edges_par().collect::<Vec<_>>();
+        edges.sort_unstable_by_key(|edge| (edge.from, edge.to));
+        edges_par.sort_unstable_by_key(|edge| (edge.from, edge.to));
+        assert_eq!(edges, edges_par);
+
+        for u in graph.vertices() {
+            assert_eq!(graph.out_degree(u), graph.neighbours(u).count());
+
+            for v in graph.vertices() {
+                assert_eq!(graph.has_edge(u, v), graph.neighbours(u).any(|w| w == v));
+            }
+        }
+    }
+}
+
+fn streamed(directed: bool, format: Format, seed: u64) -> AdjLists {
+    let mut out = vec![];
+
+    match directed {
+        true => stream::gen_directed(1000, 5000, graph::seeds(seed), format, &mut out),
+        false => stream::gen_undirected(1000, 2500, graph::seeds(seed), format, &mut out),
+    }
+    .unwrap();
+
+    format.read(&out[..]).unwrap().into_adj_lists().unwrap()
+}
+
+#[test]
+fn stream_same_graph() {
+    for &format in &[Format::Dimacs, Format::MatrixMarket, Format::Snap] {
+        let directed: AdjLists = AdjLists::gen_directed(1000, 5000, graph::seeds(3));
+        let undirected: AdjLists = AdjLists::gen_undirected(1000, 2500, graph::seeds(3));
+
+        for &(ref answer, directed) in &[(directed, true), (undirected, false)] {
+            let graph = streamed(directed, format, 3);
+
+            assert_eq!(graph.n_edges(), answer.n_edges());
+            assert!(answer.vertices().all(|v| answer.neighbours(v).eq(graph.neighbours(v))));
+        }
+    }
+}
+
+#[test]
+fn stream_metis() {
+    let err = stream::gen_directed(10, 20, None, Format::Metis, vec![]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn stream_empty() {
+    for &directed in &[true, false] {
+        let mut out = vec![];
+
+        match directed {
+            true => stream::gen_directed(0, 0, None, Format::Snap, &mut out),
+            false => stream::gen_undirected(0, 0, None, Format::Snap, &mut out),
+        }
+        .unwrap();
+
+        assert_eq!(Format::Snap.read(&out[..]).unwrap().edges.len(), 0);
+    }
+}
+
+#[test]
+fn seeds_repeat() {
+    let seeds = graph::seeds(7).take(5).collect::<Vec<_>>();
+
+    assert_eq!(seeds, graph::seeds(7).take(5).collect::<Vec<_>>());
+    assert_ne!(seeds, graph::seeds(8).take(5).collect::<Vec<_>>());
+    assert_ne!(seeds[0], seeds[1]);
+}
+
+// The sorted copy used by `has_edge` is made again after a change.
+#[test]
+fn has_edge_after_change() {
+    let mut graph: AdjLists = AdjLists::new(3);
+    graph.add_edge(Edge::new(0, 2));
+    graph.add_edge(Edge::new(0, 1));
+
+    assert!(graph.has_edge(0, 1));
+    assert!(!graph.has_edge(1, 2));
+
+    graph.add_edge(Edge::new(1, 2));
+    assert!(graph.has_edge(1, 2));
+    assert!(graph.clone().has_edge(0, 2));
+}
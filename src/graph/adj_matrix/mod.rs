@@ -1,9 +1,13 @@
 use rand::distributions::Uniform;
 use rand::prelude::*;
-use rayon::{self, prelude::*};
+use rayon::{self, prelude::*, ThreadPool};
+use std::io::{self, Write};
 use std::iter;
 
-use crate::graph::{Edge, GraphRef, Prng};
+use crate::bfs;
+use crate::dfs::{Algorithm, DfsConfig};
+use crate::graph::{Edge, Graph, GraphRef, Prng, Tree};
+use crate::progress::{self, Phase, EDGES_PER_REPORT};
 use crate::utils::BitVec;
 
 #[derive(Debug)]
@@ -47,6 +51,7 @@ impl AdjMatrix {
         I: IntoIterator<Item = <Prng as SeedableRng>::Seed>,
     {
         assert!(n_edges <= n_verts * (n_verts - 1));
+        progress::phase(Phase::Generate);
 
         let graph = Self::new(n_verts);
         const EDGES_PER_CHUNK: usize = 128;
@@ -92,6 +97,8 @@ impl AdjMatrix {
                     }
                 }
             }
+
+            progress::edges_generated(edges_to_gen);
         });
 
         graph
@@ -119,6 +126,7 @@ impl AdjMatrix {
         <I as IntoIterator>::IntoIter: Send,
     {
         assert!(n_edges <= n_verts * (n_verts - 1));
+        progress::phase(Phase::Generate);
 
         let graph = Self::new(n_verts);
         let seeds = seeds.into_iter().map(|s| Some(s)).chain(iter::repeat(None));
@@ -155,9 +163,15 @@ impl AdjMatrix {
                         if graph.should_add(from, to) {
                             if graph.data.swap(graph.index(from, to), true) == false {
                                 added += 1;
+
+                                if added % EDGES_PER_REPORT == 0 {
+                                    progress::edges_generated(EDGES_PER_REPORT);
+                                }
                             }
                         }
                     }
+
+                    progress::edges_generated(added % EDGES_PER_REPORT);
                 });
             });
         });
@@ -186,6 +200,7 @@ impl AdjMatrix {
         I: IntoIterator<Item = <Prng as SeedableRng>::Seed>,
     {
         assert!(n_edges <= n_verts * (n_verts - 1) / 2);
+        progress::phase(Phase::Generate);
 
         let graph = Self::new(n_verts);
         const EDGES_PER_CHUNK: usize = 128;
@@ -228,11 +243,48 @@ impl AdjMatrix {
                     }
                 }
             }
+
+            progress::edges_generated(edges_to_gen);
         });
 
         graph
     }
 
+    /// Same as `gen_directed`, but generates the graph on `pool` instead of
+    /// the current thread pool.
+    pub fn gen_directed_in<I>(pool: &ThreadPool, n_verts: usize, n_edges: usize, seeds: I) -> Self
+    where
+        I: IntoIterator<Item = <Prng as SeedableRng>::Seed> + Send,
+    {
+        pool.install(|| Self::gen_directed(n_verts, n_edges, seeds))
+    }
+
+    /// Same as `gen_undirected`, but generates the graph on `pool` instead of
+    /// the current thread pool.
+    pub fn gen_undirected_in<I>(pool: &ThreadPool, n_verts: usize, n_edges: usize, seeds: I) -> Self
+    where
+        I: IntoIterator<Item = <Prng as SeedableRng>::Seed> + Send,
+    {
+        pool.install(|| Self::gen_undirected(n_verts, n_edges, seeds))
+    }
+
+    /// Add the edge *(from, to)* to the graph.
+    ///
+    /// # Panics
+    ///
+    /// If the edge is a loop or already exists.
+    pub fn add_edge(&mut self, edge: Edge) {
+        assert!(edge.from != edge.to, "loops are not allowed");
+        assert!(!self.has_edge(edge.from, edge.to), "multiple edges are not allowed");
+
+        self.data.set(self.index(edge.from, edge.to), true);
+    }
+
+    /// Whether the edge *(from, to)* exists in the graph.
+    pub fn has_edge(&self, from: usize, to: usize) -> bool {
+        self.data.get(self.index(from, to)).unwrap()
+    }
+
     fn should_add(&self, from: usize, to: usize) -> bool {
         from != to && self.data.get(self.index(from, to)).unwrap() == false
     }
@@ -250,10 +302,19 @@ impl AdjMatrix {
     }
 }
 
+/// `edges_par` of `AdjMatrix`, with a function pointer instead of a closure
+/// so that the type can be named.
+type EdgesPar<'a> = rayon::iter::FlatMap<
+    rayon::iter::Zip<rayon::iter::RepeatN<&'a AdjMatrix>, rayon::range::Iter<usize>>,
+    fn((&'a AdjMatrix, usize)) -> Vec<Edge>,
+>;
+
 impl<'a> GraphRef<'a> for &'a AdjMatrix {
+    type Vertex = usize;
     type Vertices = std::ops::Range<usize>;
     type VerticesPar = rayon::range::Iter<usize>;
     type Neighbours = crate::utils::bit_vec::Ones<'a>;
+    type EdgesPar = EdgesPar<'a>;
 
     /// Iterator over all vertices in the graph.
     fn vertices(self) -> Self::Vertices {
@@ -274,6 +335,58 @@ impl<'a> GraphRef<'a> for &'a AdjMatrix {
         let end = (v + 1) * self.n_verts;
         self.data.slice(start..end).ones()
     }
+
+    fn edges_par(self) -> Self::EdgesPar {
+        fn row_edges((graph, from): (&AdjMatrix, usize)) -> Vec<Edge> {
+            graph.neighbours(from).map(|to| Edge::new(from, to)).collect()
+        }
+
+        rayon::iter::repeatn(self, self.n_verts)
+            .zip(0..self.n_verts)
+            .flat_map(row_edges as _)
+    }
+
+    fn n_verts(self) -> usize {
+        self.n_verts
+    }
+
+    /// Number of edges in the graph, i.e. the number of set bits in the matrix.
+    fn n_edges(self) -> usize {
+        self.data.count_ones()
+    }
+
+    fn out_degree(self, v: usize) -> usize {
+        let start = v * self.n_verts;
+        let end = (v + 1) * self.n_verts;
+        self.data.slice(start..end).count_ones()
+    }
+
+    fn has_edge(self, from: usize, to: usize) -> bool {
+        AdjMatrix::has_edge(self, from, to)
+    }
+}
+
+impl Graph for AdjMatrix {
+    fn traverse(&self, algorithm: Algorithm, config: &DfsConfig) -> Vec<Tree> {
+        match algorithm {
+            // Bottom-up steps need constant time edge lookup
+            Algorithm::Bfs => config.install(|| bfs::direction_optimizing(self)),
+            _ => algorithm.run_ref(self, config),
+        }
+    }
+
+    fn write_dot(
+        &self,
+        forest: Option<&[Tree]>,
+        numbers: bool,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        crate::io::write_graph_dot(self, forest, numbers, out)
+    }
+
+    fn write_binary(&self, out: &mut dyn Write) -> io::Result<()> {
+        crate::io::write_binary(self, out)
+    }
 }
 
 #[cfg(test)]
@@ -53,3 +53,19 @@ fn generate_undirected() {
     assert_eq!(graph.vertices().count(), 300);
     assert_eq!(graph.edges().count(), 20000);
 }
+
+#[test]
+fn queries() {
+    let graph = AdjMatrix::gen_undirected(100, 500, None);
+
+    assert_eq!(graph.n_verts(), 100);
+    assert_eq!(graph.n_edges(), 1000);
+
+    let mut edges_par = graph.edges_par().collect::<Vec<_>>();
+    edges_par.sort_unstable_by_key(|edge| (edge.from, edge.to));
+    assert_eq!(graph.edges().collect::<Vec<_>>(), edges_par);
+
+    for u in graph.vertices() {
+        assert_eq!(graph.out_degree(u), graph.neighbours(u).count());
+    }
+}
@@ -0,0 +1,166 @@
+//! Traversal forest stored as a graph
+//!
+//! Lets the result of one traversal be the input of another one without
+//! going through `AdjLists`.
+
+use rayon::prelude::*;
+use std::io::{self, Write};
+use std::iter;
+
+use crate::dfs::{Algorithm, DfsConfig};
+use crate::graph::adapters::ListEdges;
+use crate::graph::tree;
+use crate::graph::{Edge, Graph, GraphRef, Tree, VertexId};
+
+/// Forest in compressed sparse row form, with an edge from every vertex to
+/// each of its children.
+///
+/// The children of `v` are `children[offsets[v]..offsets[v + 1]]`, in the
+/// order their edges appear in the tree. Vertex ids which are not in the
+/// forest are isolated vertices of the graph.
+///
+/// ```ignore
+/// let forest = ForestGraph::from(dfs::par(&graph));
+/// let again = dfs::seq(&forest);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ForestGraph<V: VertexId = usize> {
+    roots: Vec<V>,
+    offsets: Vec<usize>,
+    children: Vec<V>,
+}
+
+impl<V: VertexId> ForestGraph<V> {
+    /// Build the graph of `forest`. It has as many vertices as the largest
+    /// vertex id in the forest plus one.
+    pub fn new(forest: &[Tree<V>]) -> Self {
+        let n_verts = tree::bound(forest);
+        let mut offsets = vec![0; n_verts + 1];
+
+        for tree in forest {
+            for edge in &tree.edges {
+                offsets[edge.from.index() + 1] += 1;
+            }
+        }
+
+        for v in 0..n_verts {
+            offsets[v + 1] += offsets[v];
+        }
+
+        // Next free place in the children of each vertex
+        let mut next = offsets.clone();
+        let mut children = vec![V::from_index(0); offsets[n_verts]];
+
+        for tree in forest {
+            for edge in &tree.edges {
+                let from = edge.from.index();
+                children[next[from]] = edge.to;
+                next[from] += 1;
+            }
+        }
+
+        ForestGraph {
+            roots: forest.iter().map(|tree| tree.root).collect(),
+            offsets,
+            children,
+        }
+    }
+
+    /// Roots of the trees, in the order of the original forest.
+    pub fn roots(&self) -> &[V] {
+        &self.roots
+    }
+
+    /// Children of `v`.
+    pub fn children(&self, v: V) -> &[V] {
+        &self.children[self.offsets[v.index()]..self.offsets[v.index() + 1]]
+    }
+}
+
+impl<V: VertexId> From<Vec<Tree<V>>> for ForestGraph<V> {
+    fn from(forest: Vec<Tree<V>>) -> Self {
+        ForestGraph::new(&forest)
+    }
+}
+
+impl<'a, V: VertexId> From<&'a [Tree<V>]> for ForestGraph<V> {
+    fn from(forest: &'a [Tree<V>]) -> Self {
+        ForestGraph::new(forest)
+    }
+}
+
+/// `edges_par` of `ForestGraph`.
+type ForestEdgesPar<'a, V> = rayon::iter::FlatMap<
+    rayon::iter::Zip<rayon::iter::RepeatN<&'a ForestGraph<V>>, rayon::range::Iter<usize>>,
+    fn((&'a ForestGraph<V>, usize)) -> ListEdges<'a, V>,
+>;
+
+impl<'a, V: VertexId> GraphRef<'a> for &'a ForestGraph<V> {
+    type Vertex = V;
+    type Vertices = iter::Map<std::ops::Range<usize>, fn(usize) -> V>;
+    type VerticesPar = rayon::iter::Map<rayon::range::Iter<usize>, fn(usize) -> V>;
+    type Neighbours = iter::Cloned<std::slice::Iter<'a, V>>;
+    type EdgesPar = ForestEdgesPar<'a, V>;
+
+    fn vertices(self) -> Self::Vertices {
+        (0..self.n_verts()).map(V::from_index as fn(usize) -> V)
+    }
+
+    fn vertices_par(self) -> Self::VerticesPar {
+        (0..self.n_verts()).into_par_iter().map(V::from_index as fn(usize) -> V)
+    }
+
+    fn neighbours(self, v: V) -> Self::Neighbours {
+        self.children(v).iter().cloned()
+    }
+
+    fn edges_par(self) -> Self::EdgesPar {
+        fn vertex_edges<'g, V: VertexId>(
+            (graph, from): (&'g ForestGraph<V>, usize),
+        ) -> ListEdges<'g, V> {
+            let children = graph.children(V::from_index(from));
+
+            rayon::iter::repeatn(V::from_index(from), children.len())
+                .zip(children.par_iter())
+                .map(|(from, &to)| Edge::new(from, to))
+        }
+
+        rayon::iter::repeatn(self, self.n_verts())
+            .zip(0..self.n_verts())
+            .flat_map(vertex_edges as _)
+    }
+
+    fn n_verts(self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    fn n_edges(self) -> usize {
+        self.children.len()
+    }
+
+    fn out_degree(self, v: V) -> usize {
+        self.offsets[v.index() + 1] - self.offsets[v.index()]
+    }
+}
+
+impl<V: VertexId> Graph<V> for ForestGraph<V> {
+    fn traverse(&self, algorithm: Algorithm, config: &DfsConfig) -> Vec<Tree<V>> {
+        algorithm.run_ref(self, config)
+    }
+
+    fn write_dot(
+        &self,
+        forest: Option<&[Tree<V>]>,
+        numbers: bool,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        crate::io::write_graph_dot(self, forest, numbers, out)
+    }
+
+    fn write_binary(&self, out: &mut dyn Write) -> io::Result<()> {
+        crate::io::write_binary(self, out)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,74 @@
+use crate::dfs::{self, Algorithm};
+use crate::graph::{AdjLists, Edge, Forest, GraphRef, Tree};
+use super::*;
+
+//      0
+//    /   \
+//   1     4
+//  / \
+// 2   3
+fn small() -> Tree {
+    let mut tree = Tree::new(0);
+    tree.add(Edge::new(0, 1));
+    tree.add(Edge::new(1, 2));
+    tree.add(Edge::new(1, 3));
+    tree.add(Edge::new(0, 4));
+    tree
+}
+
+#[test]
+fn structure() {
+    let graph = ForestGraph::from(vec![small(), Tree::new(5)]);
+
+    assert_eq!(graph.n_verts(), 6);
+    assert_eq!(graph.n_edges(), 4);
+    assert_eq!(graph.roots(), &[0, 5]);
+    assert_eq!(graph.children(0), &[1, 4]);
+    assert_eq!(graph.children(1), &[2, 3]);
+    assert!(graph.children(5).is_empty());
+    assert_eq!(graph.out_degree(1), 2);
+    assert!(graph.has_edge(1, 3));
+    assert!(!graph.has_edge(3, 1));
+
+    let mut edges = graph.edges_par().collect::<Vec<_>>();
+    let mut expected = small().edges;
+    edges.sort_unstable_by_key(|edge| (edge.from, edge.to));
+    expected.sort_unstable_by_key(|edge| (edge.from, edge.to));
+
+    assert_eq!(edges, expected);
+}
+
+// With ids in preorder the sequential DFS of the forest is the forest itself.
+#[test]
+fn seq_gives_back_forest() {
+    let graph = ForestGraph::from(vec![small(), Tree::new(5)]);
+    let forest = dfs::seq(&graph);
+
+    assert_eq!(forest.len(), 2);
+    assert_eq!(forest[0].root, 0);
+    assert_eq!(forest[0].edges, small().edges);
+    assert_eq!(forest[1].root, 5);
+    assert!(forest[1].edges.is_empty());
+}
+
+// Every vertex has a single incoming edge, so any traversal of the forest
+// visits each vertex once and only uses edges of the forest.
+#[test]
+fn traverse_dfs_forest() {
+    let graph: AdjLists = AdjLists::gen_directed(1000, 3000, None);
+    let forest = dfs::par(&graph);
+    let parents = forest.parent_array();
+    let forest_graph = ForestGraph::new(&forest);
+
+    assert_eq!(forest_graph.n_verts(), 1000);
+    assert_eq!(forest_graph.n_edges(), 1000 - forest.len());
+
+    for &algorithm in &Algorithm::ALL {
+        let again = algorithm.run(&forest_graph);
+        assert!(tree::is_spanning(1000, &again), "{}", algorithm);
+
+        for edge in again.iter().flat_map(|tree| &tree.edges) {
+            assert_eq!(parents[edge.to], Some(edge.from), "{}", algorithm);
+        }
+    }
+}
@@ -0,0 +1,232 @@
+//! Graph read from a memory-mapped binary file
+//!
+//! Large inputs are stored once with `io::write_binary` and then mapped by
+//! every run, instead of being generated or parsed again.
+
+use rayon::prelude::*;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Write};
+use std::iter;
+use std::marker::PhantomData;
+use std::mem;
+use std::path::Path;
+use std::slice;
+
+use crate::dfs::{Algorithm, DfsConfig};
+use crate::graph::adapters::ListEdges;
+use crate::graph::{Edge, Graph, GraphRef, Tree, VertexId};
+use crate::io::binary::{self, invalid, Header};
+use crate::io::Mapping;
+
+/// Read-only graph in compressed sparse row form, used directly from the
+/// pages of a file written by `io::write_binary`.
+///
+/// The neighbours of `v` are `targets[offsets[v]..offsets[v + 1]]`, in the
+/// order they had in the written graph. The ids in the file must have the
+/// size of `V`, e.g. a graph written from `AdjLists<u32>` is opened as
+/// `MappedGraph<u32>`.
+///
+/// ```ignore
+/// io::write_binary(&graph, BufWriter::new(File::create("graph.bin")?))?;
+/// let mapped: MappedGraph = MappedGraph::open("graph.bin".as_ref())?;
+/// mapped.verify()?;
+/// let forest = dfs::par(&mapped);
+/// ```
+pub struct MappedGraph<V: VertexId = usize> {
+    mapping: Mapping,
+    header: Header,
+    vertex: PhantomData<V>,
+}
+
+impl<V: VertexId> MappedGraph<V> {
+    /// Map the file at `path` and check its header.
+    ///
+    /// Only the header, the size of the file and the first and last offsets
+    /// are checked, so the graph is ready without reading the whole file.
+    /// Call `verify` to check the rest. A damaged file which isn't verified
+    /// can make traversals panic or give wrong results, but it is still
+    /// memory safe. Errors in the file are returned with
+    /// `io::ErrorKind::InvalidData`.
+    ///
+    /// The file must not be truncated or changed while it is mapped. Reading
+    /// pages cut off from a file raises `SIGBUS`, and changes made by other
+    /// processes may be seen by the graph even though it is mapped privately.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        if cfg!(target_endian = "big") {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "binary graphs can only be mapped on little-endian platforms",
+            ));
+        }
+
+        let mapping = Mapping::new(&File::open(path)?)?;
+        let header = Header::parse(mapping.bytes())?;
+
+        if header.id_bytes != mem::size_of::<V>() {
+            return Err(invalid(format!(
+                "the file has {} byte vertex ids, expected {}",
+                header.id_bytes,
+                mem::size_of::<V>()
+            )));
+        }
+
+        if header.n_verts > 0 && header.n_verts - 1 > V::MAX {
+            return Err(invalid(format!(
+                "{} vertices don't fit in {} byte ids",
+                header.n_verts, header.id_bytes
+            )));
+        }
+
+        let graph = MappedGraph {
+            mapping,
+            header,
+            vertex: PhantomData,
+        };
+
+        let offsets = graph.offsets();
+        let n_verts = graph.header.n_verts;
+
+        if offsets[0] != 0 || offsets[n_verts] != graph.header.n_edges as u64 {
+            return Err(invalid("the offsets don't match the number of edges".to_string()));
+        }
+
+        Ok(graph)
+    }
+
+    /// Check the checksum, the offsets and the targets of the whole file.
+    ///
+    /// Every page of the file is read, in parallel, which still takes a
+    /// fraction of the time needed to parse a text file.
+    pub fn verify(&self) -> io::Result<()> {
+        let words = self.mapping.words();
+        let (stored, words) = words.split_last().unwrap();
+
+        if binary::checksum(words) != u64::from_le(*stored) {
+            return Err(invalid("wrong checksum, the file is damaged".to_string()));
+        }
+
+        let offsets = self.offsets();
+        let n_verts = self.header.n_verts;
+
+        if let Some(v) = (0..n_verts).into_par_iter().find_any(|&v| offsets[v] > offsets[v + 1]) {
+            return Err(invalid(format!("the offsets of vertex {} are decreasing", v)));
+        }
+
+        match self.targets().par_iter().find_any(|to| to.index() >= n_verts) {
+            Some(to) => Err(invalid(format!("vertex {:?} is out of 0..{}", to, n_verts))),
+            None => Ok(()),
+        }
+    }
+
+    /// Offsets of the neighbours of each vertex in `targets`, with a last one
+    /// equal to the number of edges.
+    pub fn offsets(&self) -> &[u64] {
+        let range = self.header.offsets();
+        &self.mapping.words()[range.start / 8..range.end / 8]
+    }
+
+    /// Neighbours of all vertices, one after another.
+    pub fn targets(&self) -> &[V] {
+        let range = self.header.targets();
+
+        // `V` is an integer type with the size of the stored ids, and the
+        // targets start at a multiple of 8 bytes in a mapping aligned to 8.
+        unsafe {
+            let start = self.mapping.bytes().as_ptr().add(range.start);
+            slice::from_raw_parts(start as *const V, self.header.n_edges)
+        }
+    }
+
+    /// Neighbours of `v`.
+    pub fn list(&self, v: V) -> &[V] {
+        let offsets = self.offsets();
+        &self.targets()[offsets[v.index()] as usize..offsets[v.index() + 1] as usize]
+    }
+}
+
+impl<V: VertexId> fmt::Debug for MappedGraph<V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MappedGraph")
+            .field("n_verts", &self.header.n_verts)
+            .field("n_edges", &self.header.n_edges)
+            .finish()
+    }
+}
+
+/// `edges_par` of `MappedGraph`.
+type MappedEdgesPar<'a, V> = rayon::iter::FlatMap<
+    rayon::iter::Zip<rayon::iter::RepeatN<&'a MappedGraph<V>>, rayon::range::Iter<usize>>,
+    fn((&'a MappedGraph<V>, usize)) -> ListEdges<'a, V>,
+>;
+
+impl<'a, V: VertexId> GraphRef<'a> for &'a MappedGraph<V> {
+    type Vertex = V;
+    type Vertices = iter::Map<std::ops::Range<usize>, fn(usize) -> V>;
+    type VerticesPar = rayon::iter::Map<rayon::range::Iter<usize>, fn(usize) -> V>;
+    type Neighbours = iter::Cloned<std::slice::Iter<'a, V>>;
+    type EdgesPar = MappedEdgesPar<'a, V>;
+
+    fn vertices(self) -> Self::Vertices {
+        (0..self.n_verts()).map(V::from_index as fn(usize) -> V)
+    }
+
+    fn vertices_par(self) -> Self::VerticesPar {
+        (0..self.n_verts()).into_par_iter().map(V::from_index as fn(usize) -> V)
+    }
+
+    fn neighbours(self, v: V) -> Self::Neighbours {
+        self.list(v).iter().cloned()
+    }
+
+    fn edges_par(self) -> Self::EdgesPar {
+        fn vertex_edges<'g, V: VertexId>(
+            (graph, from): (&'g MappedGraph<V>, usize),
+        ) -> ListEdges<'g, V> {
+            let list = graph.list(V::from_index(from));
+
+            rayon::iter::repeatn(V::from_index(from), list.len())
+                .zip(list.par_iter())
+                .map(|(from, &to)| Edge::new(from, to))
+        }
+
+        rayon::iter::repeatn(self, self.n_verts())
+            .zip(0..self.n_verts())
+            .flat_map(vertex_edges as _)
+    }
+
+    fn n_verts(self) -> usize {
+        self.header.n_verts
+    }
+
+    fn n_edges(self) -> usize {
+        self.header.n_edges
+    }
+
+    fn out_degree(self, v: V) -> usize {
+        let offsets = self.offsets();
+        (offsets[v.index() + 1] - offsets[v.index()]) as usize
+    }
+}
+
+impl<V: VertexId> Graph<V> for MappedGraph<V> {
+    fn traverse(&self, algorithm: Algorithm, config: &DfsConfig) -> Vec<Tree<V>> {
+        algorithm.run_ref(self, config)
+    }
+
+    fn write_dot(
+        &self,
+        forest: Option<&[Tree<V>]>,
+        numbers: bool,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        crate::io::write_graph_dot(self, forest, numbers, out)
+    }
+
+    fn write_binary(&self, out: &mut dyn Write) -> io::Result<()> {
+        crate::io::write_binary(self, out)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,131 @@
+use crate::dfs;
+use crate::graph::{AdjLists, Edge, Forest, GraphRef};
+use crate::io::write_binary;
+use super::*;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+/// Write `graph` to a temporary file and return its path.
+fn write<'a, G: GraphRef<'a> + Copy>(graph: G, name: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("parallel-dfs-{}-{}.bin", name, process::id()));
+    let mut out = Vec::new();
+    write_binary(graph, &mut out).unwrap();
+    fs::write(&path, out).unwrap();
+    path
+}
+
+fn edges<'a, G: GraphRef<'a> + Copy>(graph: G) -> Vec<(usize, usize)> {
+    graph
+        .vertices()
+        .flat_map(|from| graph.neighbours(from).map(move |to| (from.index(), to.index())))
+        .collect()
+}
+
+#[test]
+fn same_graph() {
+    let graph: AdjLists = AdjLists::gen_directed(300, 2000, None);
+    let path = write(&graph, "same");
+    let mapped: MappedGraph = MappedGraph::open(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    mapped.verify().unwrap();
+    assert_eq!(mapped.n_verts(), 300);
+    assert_eq!(mapped.n_edges(), 2000);
+    assert_eq!(edges(&mapped), edges(&graph));
+    assert_eq!(mapped.offsets().len(), 301);
+
+    for v in graph.vertices() {
+        assert_eq!(mapped.out_degree(v), graph.out_degree(v));
+    }
+
+    assert_eq!(dfs::seq(&mapped).parent_array(), dfs::seq(&graph).parent_array());
+    assert_eq!(mapped.edges_par().count(), 2000);
+}
+
+#[test]
+fn small_ids() {
+    let mut graph: AdjLists<u32> = AdjLists::new(3);
+    graph.add_edge(Edge::new(0, 1));
+    graph.add_edge(Edge::new(2, 1));
+    graph.add_edge(Edge::new(0, 2));
+
+    let path = write(&graph, "small-ids");
+    let mapped: MappedGraph<u32> = MappedGraph::open(&path).unwrap();
+    let wrong_ids = MappedGraph::<u64>::open(&path);
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(mapped.targets(), &[1, 2, 1]);
+    assert_eq!(mapped.list(0), &[1, 2]);
+    assert!(mapped.list(1).is_empty());
+    assert!(mapped.has_edge(2, 1));
+
+    assert_eq!(wrong_ids.unwrap_err().kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn empty() {
+    let graph: AdjLists = AdjLists::new(0);
+    let path = write(&graph, "empty");
+    let mapped: MappedGraph = MappedGraph::open(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(mapped.n_verts(), 0);
+    assert!(dfs::seq(&mapped).is_empty());
+}
+
+// Damaged files are rejected instead of traversed.
+#[test]
+fn damaged() {
+    let graph: AdjLists = AdjLists::gen_directed(50, 200, None);
+    let mut bytes = Vec::new();
+    write_binary(&graph, &mut bytes).unwrap();
+
+    let open = |bytes: &[u8], name: &str, verify: bool| {
+        let path = env::temp_dir().join(format!("parallel-dfs-{}-{}.bin", name, process::id()));
+        fs::write(&path, bytes).unwrap();
+        let result = MappedGraph::<usize>::open(&path).and_then(|graph| match verify {
+            true => graph.verify(),
+            false => Ok(()),
+        });
+        fs::remove_file(&path).unwrap();
+        result.map_err(|err| err.to_string())
+    };
+    let open_err = |bytes: &[u8], name: &str| open(bytes, name, true).unwrap_err();
+
+    // Only `verify` reads the targets
+    let mut flipped = bytes.clone();
+    let last_target = flipped.len() - 16;
+    flipped[last_target] ^= 1;
+    assert!(open(&flipped, "flipped", false).is_ok());
+    assert!(open_err(&flipped, "flipped").contains("checksum"));
+
+    let truncated = &bytes[..bytes.len() - 8];
+    assert!(open_err(truncated, "truncated").contains("doesn't match"));
+
+    let mut version = bytes.clone();
+    version[8] = 2;
+    assert!(open_err(&version, "version").contains("version"));
+
+    assert!(open_err(b"not a graph file", "text").contains("not a binary graph"));
+}
+
+// The graph trait object writes the same bytes as the generic function.
+#[test]
+fn graph_trait() {
+    let graph: AdjLists = AdjLists::gen_undirected(40, 100, None);
+    let path = write(&graph, "trait");
+    let mapped: MappedGraph = MappedGraph::open(&path).unwrap();
+    let written = fs::read(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let graphs: [&dyn Graph; 2] = [&graph, &mapped];
+
+    for graph in &graphs {
+        let mut out = Vec::new();
+        graph.write_binary(&mut out).unwrap();
+        assert_eq!(out, written);
+    }
+}
@@ -1,12 +1,28 @@
+use rand::prelude::*;
 use rayon::iter::ParallelIterator;
 
+use crate::dfs::{Algorithm, DfsConfig};
+
+use std::io;
+use std::iter;
+
+mod adapters;
 pub mod adj_lists;
 pub mod adj_matrix;
+mod forest;
+mod mapped;
+mod representation;
 pub mod tree;
+mod vertex;
 
+pub use self::adapters::FnGraph;
 pub use self::adj_lists::AdjLists;
 pub use self::adj_matrix::AdjMatrix;
-pub use self::tree::Tree;
+pub use self::forest::ForestGraph;
+pub use self::mapped::MappedGraph;
+pub use self::representation::Representation;
+pub use self::tree::{Forest, LcaIndex, Numbering, Tree};
+pub use self::vertex::VertexId;
 
 /// Pseudo-random number generator algorithm used in this module.
 ///
@@ -14,25 +30,94 @@ pub use self::tree::Tree;
 /// non-cryptographically secure PRNG provided by `rand`.
 type Prng = rand::prng::XorShiftRng;
 
+/// Seeds for the `seeds` argument of the generators, all derived from `seed`.
+///
+/// The same `seed` gives the same graph from `AdjLists::gen_directed` and
+/// `adj_lists::stream::gen_directed`, and likewise for undirected graphs.
+pub fn seeds(seed: u64) -> impl Iterator<Item = <Prng as SeedableRng>::Seed> {
+    let mut state = [0; 16];
+    state[..8].copy_from_slice(&seed.to_le_bytes());
+
+    let mut rng = Prng::from_seed(state);
+    iter::repeat_with(move || rng.gen())
+}
+
 /// Graph edge.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Edge {
-    pub from: usize,
-    pub to: usize,
+pub struct Edge<V: VertexId = usize> {
+    pub from: V,
+    pub to: V,
 }
 
-impl Edge {
-    pub fn new(from: usize, to: usize) -> Self {
+impl<V: VertexId> Edge<V> {
+    pub fn new(from: V, to: V) -> Self {
         Edge { from, to }
     }
 }
 
-pub trait GraphRef<'a> {
-    type Vertices: Iterator<Item = usize> + DoubleEndedIterator + 'a;
-    type VerticesPar: ParallelIterator<Item = usize> + 'a;
-    type Neighbours: Iterator<Item = usize> + DoubleEndedIterator + 'a;
+pub trait GraphRef<'a>: Sized {
+    type Vertex: VertexId;
+    type Vertices: Iterator<Item = Self::Vertex> + DoubleEndedIterator + 'a;
+    type VerticesPar: ParallelIterator<Item = Self::Vertex> + 'a;
+    type Neighbours: Iterator<Item = Self::Vertex> + DoubleEndedIterator + 'a;
+    type EdgesPar: ParallelIterator<Item = Edge<Self::Vertex>> + 'a;
 
     fn vertices(self) -> Self::Vertices;
     fn vertices_par(self) -> Self::VerticesPar;
-    fn neighbours(self, v: usize) -> Self::Neighbours;
+    fn neighbours(self, v: Self::Vertex) -> Self::Neighbours;
+
+    /// Rayon parallel iterator over all edges in the graph.
+    fn edges_par(self) -> Self::EdgesPar;
+
+    /// Number of vertices in the graph.
+    ///
+    /// The default counts the vertices. The graphs in this crate override it
+    /// with an O(1) version and so should other implementations if they can.
+    fn n_verts(self) -> usize {
+        self.vertices().count()
+    }
+
+    /// Number of edges in the graph. The default sums the degrees.
+    fn n_edges(self) -> usize
+    where
+        Self: Copy,
+    {
+        self.vertices().map(|v| self.out_degree(v)).sum()
+    }
+
+    /// Number of edges going out of `v`.
+    fn out_degree(self, v: Self::Vertex) -> usize {
+        self.neighbours(v).count()
+    }
+
+    /// Whether the edge *(from, to)* exists in the graph.
+    fn has_edge(self, from: Self::Vertex, to: Self::Vertex) -> bool {
+        self.neighbours(from).any(|v| v == to)
+    }
+}
+
+/// Graph which can be traversed through a trait object.
+///
+/// `GraphRef` is generic over its iterators, so it can't be used as a trait
+/// object. This trait dispatches to the concrete type instead, which lets the
+/// algorithm and the representation both be chosen at runtime.
+pub trait Graph<V: VertexId = usize>: Sync {
+    /// Build a traversal forest with `algorithm`.
+    ///
+    /// Implementations usually call `Algorithm::run_ref`, but may use an
+    /// algorithm specific to the representation instead.
+    fn traverse(&self, algorithm: Algorithm, config: &DfsConfig) -> Vec<Tree<V>>;
+
+    /// Write the graph in the GraphViz DOT format, with `forest` drawn over
+    /// it if given. See `io::write_forest_dot`.
+    fn write_dot(
+        &self,
+        forest: Option<&[Tree<V>]>,
+        numbers: bool,
+        out: &mut dyn io::Write,
+    ) -> io::Result<()>;
+
+    /// Write the graph in the binary format of `io::write_binary`, which
+    /// `MappedGraph` opens.
+    fn write_binary(&self, out: &mut dyn io::Write) -> io::Result<()>;
 }
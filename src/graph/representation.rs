@@ -0,0 +1,82 @@
+use crate::graph::{self, AdjLists, AdjMatrix, Graph};
+
+use std::fmt;
+use std::str::FromStr;
+
+/// All graph representations which can be generated.
+///
+/// Together with `dfs::Algorithm` it lets the command line, the tests and the
+/// benchmarks go over every combination without listing them by hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Representation {
+    Lists,
+    Matrix,
+}
+
+impl Representation {
+    pub const ALL: [Representation; 2] = [Representation::Lists, Representation::Matrix];
+
+    /// Short name used on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Representation::Lists => "list",
+            Representation::Matrix => "mat",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Representation::Lists => "adjacency lists",
+            Representation::Matrix => "adjacency matrix",
+        }
+    }
+
+    /// Generate a random graph with `n_verts` vertices and `n_edges` edges,
+    /// or `2 * n_edges` if it is undirected.
+    ///
+    /// Seeds are derived from `seed` with `graph::seeds`, or random if it is
+    /// `None`. Adjacency lists are the same for the same seed, but matrix
+    /// generators race between chunks, so a matrix may still differ.
+    pub fn generate(
+        self,
+        n_verts: usize,
+        n_edges: usize,
+        undirected: bool,
+        seed: Option<u64>,
+    ) -> Box<dyn Graph> {
+        let seeds = seed.into_iter().flat_map(graph::seeds);
+
+        match (self, undirected) {
+            (Representation::Lists, true) => {
+                Box::new(AdjLists::<usize>::gen_undirected(n_verts, n_edges, seeds))
+            },
+            (Representation::Lists, false) => {
+                Box::new(AdjLists::<usize>::gen_directed(n_verts, n_edges, seeds))
+            },
+            (Representation::Matrix, true) => {
+                Box::new(AdjMatrix::gen_undirected(n_verts, n_edges, seeds))
+            },
+            (Representation::Matrix, false) => {
+                Box::new(AdjMatrix::gen_directed(n_verts, n_edges, seeds))
+            },
+        }
+    }
+}
+
+impl fmt::Display for Representation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Representation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Representation::ALL
+            .iter()
+            .cloned()
+            .find(|representation| representation.name() == s)
+            .ok_or_else(|| format!("unknown representation {:?}", s))
+    }
+}
@@ -0,0 +1,137 @@
+use rayon::prelude::*;
+
+use crate::graph::{ForestGraph, GraphRef, Tree, VertexId};
+
+use std::mem;
+use std::slice;
+
+use super::NONE;
+
+/// Lowest common ancestor index of a tree.
+///
+/// Built from the Euler tour of the tree and a sparse table of range minimums
+/// over it, so queries take O(1) time and the index O(n log n) memory. The
+/// levels of the sparse table are computed in parallel.
+///
+/// ```ignore
+/// let index = LcaIndex::new(&forest[0]);
+/// let lca = index.lca(u, v);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LcaIndex<V: VertexId = usize> {
+    /// First and last position of each vertex in the tour, `NONE` for the ids
+    /// which are not in the tree.
+    first: Vec<usize>,
+    last: Vec<usize>,
+
+    depths: Vec<usize>,
+
+    /// `table[k][i]` is the vertex of the smallest depth among the tour
+    /// positions `i..i + 2^k`. `table[0]` is the tour itself.
+    table: Vec<Vec<V>>,
+}
+
+impl<V: VertexId> LcaIndex<V> {
+    pub fn new(tree: &Tree<V>) -> Self {
+        let graph = ForestGraph::new(slice::from_ref(tree));
+        let n_verts = graph.n_verts();
+
+        let mut first = vec![NONE; n_verts];
+        let mut last = vec![NONE; n_verts];
+        let mut depths = vec![NONE; n_verts];
+        let mut tour = Vec::with_capacity(2 * tree.edges.len() + 1);
+
+        // Vertex and number of its children already visited
+        let mut stack = vec![(tree.root, 0)];
+        first[tree.root.index()] = 0;
+        depths[tree.root.index()] = 0;
+
+        while let Some(&mut (v, ref mut next)) = stack.last_mut() {
+            last[v.index()] = tour.len();
+            tour.push(v);
+
+            match graph.children(v).get(*next) {
+                Some(&child) => {
+                    *next += 1;
+                    first[child.index()] = tour.len();
+                    depths[child.index()] = stack.len();
+                    stack.push((child, 0));
+                },
+                // The parent is written again after the child is done
+                None => {
+                    stack.pop();
+                },
+            }
+        }
+
+        let mut table = vec![tour];
+
+        while 1 << table.len() <= table[0].len() {
+            let prev = table.last().unwrap();
+            let half = 1 << (table.len() - 1);
+
+            let level = (0..prev.len() - half)
+                .into_par_iter()
+                .map(|i| shallower(&depths, prev[i], prev[i + half]))
+                .collect();
+
+            table.push(level);
+        }
+
+        LcaIndex {
+            first,
+            last,
+            depths,
+            table,
+        }
+    }
+
+    /// Lowest common ancestor of `u` and `v`, or `None` if one of them is not
+    /// in the tree.
+    pub fn lca(&self, u: V, v: V) -> Option<V> {
+        let (u, v) = (self.position(u)?, self.position(v)?);
+        let (start, end) = (u.min(v), u.max(v) + 1);
+
+        let k = log2(end - start);
+        let level = &self.table[k];
+
+        Some(shallower(&self.depths, level[start], level[end - (1 << k)]))
+    }
+
+    /// Whether `u` is an ancestor of `v`. Every vertex is its own ancestor.
+    pub fn is_ancestor(&self, u: V, v: V) -> bool {
+        match (self.position(u), self.position(v)) {
+            (Some(first_u), Some(first_v)) => {
+                first_u <= first_v && self.last[v.index()] <= self.last[u.index()]
+            },
+            _ => false,
+        }
+    }
+
+    /// Number of edges on the path between `u` and `v`, or `None` if one of
+    /// them is not in the tree.
+    pub fn distance(&self, u: V, v: V) -> Option<usize> {
+        let lca = self.lca(u, v)?;
+
+        Some(self.depths[u.index()] + self.depths[v.index()] - 2 * self.depths[lca.index()])
+    }
+
+    /// First position of `v` in the tour.
+    fn position(&self, v: V) -> Option<usize> {
+        match self.first.get(v.index()) {
+            Some(&first) if first != NONE => Some(first),
+            _ => None,
+        }
+    }
+}
+
+fn shallower<V: VertexId>(depths: &[usize], u: V, v: V) -> V {
+    match depths[u.index()] <= depths[v.index()] {
+        true => u,
+        false => v,
+    }
+}
+
+fn log2(x: usize) -> usize {
+    mem::size_of::<usize>() * 8 - 1 - x.leading_zeros() as usize
+}
@@ -0,0 +1,335 @@
+use rayon::prelude::*;
+
+use crate::graph::{Edge, GraphRef, VertexId};
+
+use std::slice;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+mod lca;
+mod order;
+
+pub use self::lca::LcaIndex;
+pub use self::order::Numbering;
+
+/// Marks a vertex id which is not in the forest.
+const NONE: usize = usize::MAX;
+
+/// Levels with fewer vertices than this are processed sequentially by
+/// `subtree_sizes`.
+const LEVEL_CUTOFF: usize = 1024;
+
+/// Simple tree representation.
+///
+/// Represents a tree by the root node and a flat list of tree edges.
+///
+#[derive(Debug, Clone)]
+pub struct Tree<V: VertexId = usize> {
+    pub root: V,
+    pub edges: Vec<Edge<V>>,
+}
+
+impl<V: VertexId> Tree<V> {
+    pub fn new(root: V) -> Self {
+        Tree { root, edges: vec![] }
+    }
+
+    pub fn add(&mut self, edge: Edge<V>) {
+        self.edges.push(edge);
+    }
+
+    /// See `Forest::parent_array`.
+    pub fn parent_array(&self) -> Vec<Option<V>> {
+        slice::from_ref(self).parent_array()
+    }
+
+    /// See `Forest::children`.
+    pub fn children(&self) -> Vec<Vec<V>> {
+        slice::from_ref(self).children()
+    }
+
+    /// See `Forest::depths`.
+    pub fn depths(&self) -> Vec<Option<usize>> {
+        slice::from_ref(self).depths()
+    }
+
+    /// See `Forest::subtree_sizes`.
+    pub fn subtree_sizes(&self) -> Vec<usize> {
+        slice::from_ref(self).subtree_sizes()
+    }
+
+    /// See `Forest::height`.
+    pub fn height(&self) -> usize {
+        slice::from_ref(self).height()
+    }
+
+    /// See `Forest::path_to_root`.
+    pub fn path_to_root(&self, v: V) -> Option<Vec<V>> {
+        slice::from_ref(self).path_to_root(v)
+    }
+
+    /// See `Forest::numbering`.
+    pub fn numbering<'a, G>(&self, graph: G) -> Numbering
+    where
+        G: GraphRef<'a, Vertex = V> + Copy + Sync,
+    {
+        slice::from_ref(self).numbering(graph)
+    }
+}
+
+/// Structure of a forest, such as the result of a traversal.
+///
+/// The per vertex results are indexed by vertex id and have length one more
+/// than the largest id in the forest. Ids which are not in any tree get
+/// `None`, or `0` for the subtree sizes.
+///
+/// ```ignore
+/// let forest = dfs::par(&graph);
+/// let depths = forest.depths();
+/// ```
+pub trait Forest<V: VertexId> {
+    /// Parent of each vertex, `None` for the roots.
+    fn parent_array(&self) -> Vec<Option<V>>;
+
+    /// Children of each vertex, in the order their edges appear in the tree.
+    fn children(&self) -> Vec<Vec<V>>;
+
+    /// Number of edges from each vertex to the root of its tree.
+    ///
+    /// Computed with pointer jumping, so it takes `O(log height)` parallel
+    /// rounds even for path-like trees.
+    fn depths(&self) -> Vec<Option<usize>>;
+
+    /// Number of vertices in the subtree of each vertex, including itself.
+    fn subtree_sizes(&self) -> Vec<usize>;
+
+    /// Largest depth of a vertex, `0` for an empty forest.
+    fn height(&self) -> usize;
+
+    /// Vertices from `v` up to the root of its tree, both included, or `None`
+    /// if `v` is not in the forest.
+    ///
+    /// Builds the parent array on each call, so for many queries it is better
+    /// to use `parent_array` directly.
+    fn path_to_root(&self, v: V) -> Option<Vec<V>>;
+
+    /// Pre-order and post-order numbers of a DFS of the forest which visits
+    /// the children of each vertex in the order they appear among its
+    /// neighbours in `graph`.
+    ///
+    /// `dfs::par` and `dfs::cheat` give the edges of a tree in an order which
+    /// depends on the threads. This recovers the order of the traversal from
+    /// the shape of the trees alone. Every tree edge must be an edge of
+    /// `graph`.
+    fn numbering<'a, G>(&self, graph: G) -> Numbering
+    where
+        G: GraphRef<'a, Vertex = V> + Copy + Sync;
+}
+
+impl<V: VertexId> Forest<V> for [Tree<V>] {
+    fn parent_array(&self) -> Vec<Option<V>> {
+        links(self)
+            .into_par_iter()
+            .enumerate()
+            .map(|(v, parent)| match parent == NONE || parent == v {
+                true => None,
+                false => Some(V::from_index(parent)),
+            })
+            .collect()
+    }
+
+    fn children(&self) -> Vec<Vec<V>> {
+        let mut children = vec![Vec::new(); bound(self)];
+
+        for tree in self {
+            for edge in &tree.edges {
+                children[edge.from.index()].push(edge.to);
+            }
+        }
+
+        children
+    }
+
+    fn depths(&self) -> Vec<Option<usize>> {
+        // `(next, dist)`: `next` is an ancestor, `dist` the number of edges to
+        // it. Each round doubles the distance, until `next` is the root.
+        let mut jumps = links(self)
+            .into_par_iter()
+            .enumerate()
+            .map(|(v, parent)| (parent, (parent != v) as usize))
+            .collect::<Vec<_>>();
+
+        loop {
+            let next_jumps = jumps
+                .par_iter()
+                .map(|&(next, dist)| match next == NONE {
+                    true => (NONE, 0),
+                    false => (jumps[next].0, dist + jumps[next].1),
+                })
+                .collect::<Vec<_>>();
+
+            let done = jumps
+                .par_iter()
+                .zip(&next_jumps)
+                .all(|(jump, next_jump)| jump.0 == next_jump.0);
+
+            jumps = next_jumps;
+
+            if done {
+                break;
+            }
+        }
+
+        jumps
+            .into_par_iter()
+            .map(|(next, dist)| match next == NONE {
+                true => None,
+                false => Some(dist),
+            })
+            .collect()
+    }
+
+    fn subtree_sizes(&self) -> Vec<usize> {
+        let links = links(self);
+        let depths = self.depths();
+
+        let sizes = links
+            .par_iter()
+            .map(|&parent| AtomicUsize::new((parent != NONE) as usize))
+            .collect::<Vec<_>>();
+
+        let mut by_depth = depths
+            .into_par_iter()
+            .enumerate()
+            .filter_map(|(v, depth)| depth.map(|depth| (depth, v)))
+            .collect::<Vec<_>>();
+
+        by_depth.par_sort_unstable();
+
+        // Going from the deepest level up, a level is complete once all
+        // levels below it have added their sizes to their parents.
+        let add_to_parent = |&(_, v): &(usize, usize)| {
+            let parent = links[v];
+
+            if parent != v {
+                let size = sizes[v].load(Ordering::Relaxed);
+                sizes[parent].fetch_add(size, Ordering::Relaxed);
+            }
+        };
+
+        let mut end = by_depth.len();
+
+        while end > 0 {
+            let depth = by_depth[end - 1].0;
+            let start = by_depth[..end]
+                .iter()
+                .rposition(|&(d, _)| d != depth)
+                .map_or(0, |i| i + 1);
+
+            let level = &by_depth[start..end];
+
+            match level.len() < LEVEL_CUTOFF {
+                true => level.iter().for_each(add_to_parent),
+                false => level.par_iter().for_each(add_to_parent),
+            }
+
+            end = start;
+        }
+
+        into_inner(sizes)
+    }
+
+    fn height(&self) -> usize {
+        self.depths()
+            .into_par_iter()
+            .filter_map(|depth| depth)
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn path_to_root(&self, v: V) -> Option<Vec<V>> {
+        let links = links(self);
+        let mut current = v.index();
+
+        if current >= links.len() || links[current] == NONE {
+            return None;
+        }
+
+        let mut path = vec![v];
+
+        while links[current] != current {
+            current = links[current];
+            path.push(V::from_index(current));
+        }
+
+        Some(path)
+    }
+
+    fn numbering<'a, G>(&self, graph: G) -> Numbering
+    where
+        G: GraphRef<'a, Vertex = V> + Copy + Sync,
+    {
+        order::numbering(self, graph)
+    }
+}
+
+/// One more than the largest vertex id in the forest.
+pub(crate) fn bound<V: VertexId>(forest: &[Tree<V>]) -> usize {
+    forest
+        .par_iter()
+        .map(|tree| {
+            tree.edges
+                .iter()
+                .map(|edge| edge.to.index())
+                .fold(tree.root.index(), usize::max)
+                + 1
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Parent of each vertex, with roots pointing to themselves and `NONE` for
+/// the ids which are not in the forest.
+fn links<V: VertexId>(forest: &[Tree<V>]) -> Vec<usize> {
+    let links = atomic_vec(bound(forest), NONE);
+
+    forest.par_iter().for_each(|tree| {
+        links[tree.root.index()].store(tree.root.index(), Ordering::Relaxed);
+
+        tree.edges.par_iter().for_each(|edge| {
+            links[edge.to.index()].store(edge.from.index(), Ordering::Relaxed);
+        });
+    });
+
+    into_inner(links)
+}
+
+fn atomic_vec(len: usize, value: usize) -> Vec<AtomicUsize> {
+    (0..len)
+        .into_par_iter()
+        .map(|_| AtomicUsize::new(value))
+        .collect()
+}
+
+fn into_inner(vec: Vec<AtomicUsize>) -> Vec<usize> {
+    vec.into_par_iter().map(AtomicUsize::into_inner).collect()
+}
+
+/// Whether every vertex below `n_verts` is in exactly one tree of `forest`,
+/// as either a root or the end of a tree edge.
+#[cfg(test)]
+pub(crate) fn is_spanning(n_verts: usize, forest: &[Tree]) -> bool {
+    let mut visited = vec![0; n_verts];
+
+    for tree in forest {
+        visited[tree.root] += 1;
+
+        for edge in &tree.edges {
+            visited[edge.to] += 1;
+        }
+    }
+
+    visited.iter().all(|&v| v == 1)
+}
+
+#[cfg(test)]
+mod tests;
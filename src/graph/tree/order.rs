@@ -0,0 +1,188 @@
+use rayon::prelude::*;
+
+use crate::graph::{GraphRef, Tree, VertexId};
+
+use std::collections::HashSet;
+use std::sync::atomic::Ordering;
+
+use super::{atomic_vec, into_inner, links, NONE};
+
+/// Pre-order and post-order number of each vertex, indexed by vertex id.
+///
+/// Trees are numbered one after the other, in the order of the forest. Ids
+/// which are not in the forest get `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Numbering {
+    pub pre: Vec<Option<usize>>,
+    pub post: Vec<Option<usize>>,
+}
+
+/// Number the vertices as if the trees were traversed visiting the children
+/// of each vertex in the order they appear among its neighbours in `graph`.
+///
+/// The tour of the forest is made of a down and an up arc for each tree edge
+/// and is ranked with pointer jumping, which takes `O(log n_edges)` parallel
+/// rounds.
+pub(super) fn numbering<'a, G>(forest: &[Tree<G::Vertex>], graph: G) -> Numbering
+where
+    G: GraphRef<'a> + Copy + Sync,
+{
+    let links = links(forest);
+    let n_verts = links.len();
+
+    // Edges get the indices `starts[i]..starts[i] + edges.len()` for tree `i`
+    let mut starts = Vec::with_capacity(forest.len());
+    let mut n_edges = 0;
+
+    for tree in forest {
+        starts.push(n_edges);
+        n_edges += tree.edges.len();
+    }
+
+    let edges = forest
+        .par_iter()
+        .flat_map(|tree| tree.edges.par_iter())
+        .map(|edge| (edge.from.index(), edge.to.index()))
+        .collect::<Vec<_>>();
+
+    // Index of the edge into each vertex, the tree it belongs to and the
+    // number of its children
+    let edge_of = atomic_vec(n_verts, NONE);
+    let tree_of = atomic_vec(n_verts, NONE);
+    let n_children = atomic_vec(n_verts, 0);
+
+    forest
+        .par_iter()
+        .zip(&starts)
+        .enumerate()
+        .for_each(|(i, (tree, &start))| {
+            tree_of[tree.root.index()].store(i, Ordering::Relaxed);
+
+            tree.edges.par_iter().enumerate().for_each(|(j, edge)| {
+                edge_of[edge.to.index()].store(start + j, Ordering::Relaxed);
+                tree_of[edge.to.index()].store(i, Ordering::Relaxed);
+                n_children[edge.from.index()].fetch_add(1, Ordering::Relaxed);
+            });
+        });
+
+    let edge_of = into_inner(edge_of);
+    let tree_of = into_inner(tree_of);
+    let n_children = into_inner(n_children);
+
+    // Children in the order of the neighbours
+    let first_child = atomic_vec(n_verts, NONE);
+    let next_sibling = atomic_vec(n_verts, NONE);
+
+    (0..n_verts)
+        .into_par_iter()
+        .filter(|&v| n_children[v] > 0)
+        .for_each(|v| {
+            let children = ordered_children(graph, &links, v, n_children[v]);
+
+            first_child[v].store(children[0], Ordering::Relaxed);
+
+            for pair in children.windows(2) {
+                next_sibling[pair[0]].store(pair[1], Ordering::Relaxed);
+            }
+        });
+
+    let first_child = into_inner(first_child);
+    let next_sibling = into_inner(next_sibling);
+
+    // First arc of the tour of the next tree which has any edges
+    let mut next_tour = vec![NONE; forest.len()];
+    let mut tour = NONE;
+
+    for (i, tree) in forest.iter().enumerate().rev() {
+        next_tour[i] = tour;
+
+        if !tree.edges.is_empty() {
+            tour = 2 * edge_of[first_child[tree.root.index()]];
+        }
+    }
+
+    // Arc `2 * e` goes down edge `e` and arc `2 * e + 1` back up
+    let successor = |arc: usize| {
+        let (parent, child) = edges[arc / 2];
+
+        match arc % 2 == 0 {
+            true => match first_child[child] {
+                NONE => arc + 1,
+                first => 2 * edge_of[first],
+            },
+            false => match (next_sibling[child], links[parent] == parent) {
+                (NONE, true) => next_tour[tree_of[parent]],
+                (NONE, false) => 2 * edge_of[parent] + 1,
+                (sibling, _) => 2 * edge_of[sibling],
+            },
+        }
+    };
+
+    // `(next, down, up)`: number of down and up arcs from the arc, included,
+    // to `next`, excluded. Each round doubles the distance, until `next` is
+    // past the end of the tour.
+    let mut jumps = (0..2 * n_edges)
+        .into_par_iter()
+        .map(|arc| (successor(arc), 1 - arc % 2, arc % 2))
+        .collect::<Vec<_>>();
+
+    while jumps.par_iter().any(|jump| jump.0 != NONE) {
+        jumps = jumps
+            .par_iter()
+            .map(|&(next, down, up)| match next == NONE {
+                true => (next, down, up),
+                false => {
+                    let (next_next, next_down, next_up) = jumps[next];
+                    (next_next, down + next_down, up + next_up)
+                },
+            })
+            .collect();
+    }
+
+    // Each tree before `i` has one more vertex than edges
+    let number = |v: usize| match links[v] {
+        NONE => None,
+        root if root == v => {
+            let i = tree_of[v];
+            let pre = starts[i] + i;
+            Some((pre, pre + forest[i].edges.len()))
+        },
+        _ => {
+            let (i, edge) = (tree_of[v], edge_of[v]);
+            let down_before = n_edges - jumps[2 * edge].1;
+            let up_before = n_edges - jumps[2 * edge + 1].2;
+            Some((i + down_before + 1, i + up_before))
+        },
+    };
+
+    let numbers = (0..n_verts).into_par_iter().map(number).collect::<Vec<_>>();
+
+    Numbering {
+        pre: numbers.par_iter().map(|number| number.map(|(pre, _)| pre)).collect(),
+        post: numbers.par_iter().map(|number| number.map(|(_, post)| post)).collect(),
+    }
+}
+
+/// Children of `v`, in the order of its neighbours in `graph`.
+fn ordered_children<'a, G: GraphRef<'a>>(
+    graph: G,
+    links: &[usize],
+    v: usize,
+    n_children: usize,
+) -> Vec<usize> {
+    let mut children = graph
+        .neighbours(G::Vertex::from_index(v))
+        .map(|u| u.index())
+        .filter(|&u| u != v && links.get(u) == Some(&v))
+        .collect::<Vec<_>>();
+
+    // Only with parallel edges
+    if children.len() > n_children {
+        let mut seen = HashSet::new();
+        children.retain(|&u| seen.insert(u));
+    }
+
+    assert_eq!(children.len(), n_children, "tree edges of {} are not in the graph", v);
+
+    children
+}
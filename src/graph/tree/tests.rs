@@ -0,0 +1,285 @@
+use crate::dfs;
+use crate::graph::{AdjLists, Edge, Forest, GraphRef, LcaIndex, Numbering, Tree};
+
+use std::iter;
+
+//      0
+//    /   \
+//   1     5
+//  / \
+// 2   4
+//
+// Vertex 3 is not in the tree.
+fn small() -> Tree {
+    let mut tree = Tree::new(0);
+    tree.add(Edge::new(0, 1));
+    tree.add(Edge::new(1, 2));
+    tree.add(Edge::new(1, 4));
+    tree.add(Edge::new(0, 5));
+    tree
+}
+
+fn path(n: usize) -> Tree {
+    let mut tree = Tree::new(0);
+
+    for v in 1..n {
+        tree.add(Edge::new(v - 1, v));
+    }
+
+    tree
+}
+
+#[test]
+fn small_tree() {
+    let tree = small();
+
+    assert_eq!(tree.parent_array(), vec![None, Some(0), Some(1), None, Some(1), Some(0)]);
+    assert_eq!(tree.children(), vec![vec![1, 5], vec![2, 4], vec![], vec![], vec![], vec![]]);
+    assert_eq!(tree.depths(), vec![Some(0), Some(1), Some(2), None, Some(2), Some(1)]);
+    assert_eq!(tree.subtree_sizes(), vec![5, 3, 1, 0, 1, 1]);
+    assert_eq!(tree.height(), 2);
+    assert_eq!(tree.path_to_root(4), Some(vec![4, 1, 0]));
+    assert_eq!(tree.path_to_root(0), Some(vec![0]));
+    assert_eq!(tree.path_to_root(3), None);
+    assert_eq!(tree.path_to_root(10), None);
+}
+
+#[test]
+fn empty_forest() {
+    let forest: Vec<Tree> = vec![];
+
+    assert!(forest.depths().is_empty());
+    assert_eq!(forest.height(), 0);
+}
+
+// Many pointer jumping rounds and only levels of a single vertex.
+#[test]
+fn long_path() {
+    let n = 10_000;
+    let tree = path(n);
+
+    assert_eq!(tree.height(), n - 1);
+    assert_eq!(tree.depths(), (0..n).map(Some).collect::<Vec<_>>());
+    assert_eq!(tree.subtree_sizes(), (1..=n).rev().collect::<Vec<_>>());
+    assert_eq!(tree.path_to_root(n - 1).unwrap().len(), n);
+}
+
+// A level big enough to be processed in parallel.
+#[test]
+fn wide_tree() {
+    let n = 5000;
+    let mut tree = Tree::new(0);
+
+    for v in 1..n {
+        tree.add(Edge::new(0, v));
+    }
+
+    for v in 1..n {
+        tree.add(Edge::new(v, n + v));
+    }
+
+    let sizes = tree.subtree_sizes();
+
+    assert_eq!(tree.height(), 2);
+    assert_eq!(sizes[0], 2 * n - 1);
+    assert!(sizes[1..n].iter().all(|&size| size == 2));
+    assert!(sizes[n + 1..].iter().all(|&size| size == 1));
+}
+
+// Compare with values computed from the children lists on a forest covering
+// a whole graph.
+#[test]
+fn forest_matches_sequential() {
+    let graph: AdjLists = AdjLists::gen_directed(2000, 3000, None);
+    let forest = dfs::par(&graph);
+
+    let parents = forest.parent_array();
+    let children = forest.children();
+    let depths = forest.depths();
+    let sizes = forest.subtree_sizes();
+
+    assert_eq!(depths.len(), 2000);
+
+    // Children come before their parents when going over the trees in
+    // reverse BFS order
+    let mut order = vec![];
+    let mut expected_depths = vec![None; 2000];
+
+    for tree in &forest {
+        assert_eq!(parents[tree.root], None);
+        expected_depths[tree.root] = Some(0);
+        order.push(tree.root);
+    }
+
+    let mut i = 0;
+
+    while i < order.len() {
+        let v = order[i];
+
+        for &child in &children[v] {
+            assert_eq!(parents[child], Some(v));
+            expected_depths[child] = Some(expected_depths[v].unwrap() + 1);
+            order.push(child);
+        }
+
+        i += 1;
+    }
+
+    assert_eq!(order.len(), 2000);
+    assert_eq!(depths, expected_depths);
+
+    let mut expected_sizes = vec![1; 2000];
+
+    for &v in order.iter().rev() {
+        if let Some(parent) = parents[v] {
+            expected_sizes[parent] += expected_sizes[v];
+        }
+    }
+
+    assert_eq!(sizes, expected_sizes);
+    assert_eq!(forest.height(), depths.iter().filter_map(|&d| d).max().unwrap());
+}
+
+#[test]
+fn lca_small_tree() {
+    let index = LcaIndex::new(&small());
+
+    assert_eq!(index.lca(2, 4), Some(1));
+    assert_eq!(index.lca(2, 5), Some(0));
+    assert_eq!(index.lca(1, 2), Some(1));
+    assert_eq!(index.lca(4, 4), Some(4));
+    assert_eq!(index.lca(2, 3), None);
+
+    assert!(index.is_ancestor(0, 4));
+    assert!(index.is_ancestor(1, 1));
+    assert!(!index.is_ancestor(4, 1));
+    assert!(!index.is_ancestor(5, 2));
+    assert!(!index.is_ancestor(3, 2));
+
+    assert_eq!(index.distance(2, 5), Some(3));
+    assert_eq!(index.distance(2, 4), Some(2));
+    assert_eq!(index.distance(0, 0), Some(0));
+    assert_eq!(index.distance(0, 10), None);
+}
+
+#[test]
+fn lca_single_vertex() {
+    let index: LcaIndex = LcaIndex::new(&Tree::new(3));
+
+    assert_eq!(index.lca(3, 3), Some(3));
+    assert_eq!(index.lca(0, 3), None);
+}
+
+// Compare with the last common vertex of the paths to the root.
+#[test]
+fn lca_matches_paths() {
+    let graph: AdjLists = AdjLists::gen_undirected(500, 1000, None);
+    let forest = dfs::seq(&graph);
+    let tree = forest.iter().max_by_key(|tree| tree.edges.len()).unwrap();
+    let index = LcaIndex::new(tree);
+
+    let mut vertices = vec![tree.root];
+    vertices.extend(tree.edges.iter().map(|edge| edge.to));
+
+    for &u in vertices.iter().step_by(7) {
+        for &v in vertices.iter().step_by(5) {
+            let path_u = tree.path_to_root(u).unwrap();
+            let path_v = tree.path_to_root(v).unwrap();
+
+            let common = path_u
+                .iter()
+                .rev()
+                .zip(path_v.iter().rev())
+                .take_while(|(a, b)| a == b)
+                .count();
+
+            let lca = path_u[path_u.len() - common];
+
+            assert_eq!(index.lca(u, v), Some(lca));
+            assert_eq!(index.is_ancestor(u, v), lca == u);
+            assert_eq!(index.distance(u, v), Some(path_u.len() + path_v.len() - 2 * common));
+        }
+    }
+}
+
+/// Pre-order and post-order numbers from a sequential traversal of the
+/// children lists.
+fn numbering_seq(forest: &[Tree], children: &[Vec<usize>]) -> Numbering {
+    let mut pre = vec![None; children.len()];
+    let mut post = vec![None; children.len()];
+    let (mut next_pre, mut next_post) = (0, 0);
+
+    for tree in forest {
+        let mut stack = vec![(tree.root, 0)];
+        pre[tree.root] = Some(next_pre);
+        next_pre += 1;
+
+        while let Some(&mut (v, ref mut next)) = stack.last_mut() {
+            match children[v].get(*next) {
+                Some(&child) => {
+                    *next += 1;
+                    pre[child] = Some(next_pre);
+                    next_pre += 1;
+                    stack.push((child, 0));
+                },
+                None => {
+                    post[v] = Some(next_post);
+                    next_post += 1;
+                    stack.pop();
+                },
+            }
+        }
+    }
+
+    Numbering { pre, post }
+}
+
+#[test]
+fn numbering_small_tree() {
+    let graph = vec![vec![5, 1], vec![4, 2], vec![], vec![], vec![], vec![]];
+    let numbering = small().numbering(&graph);
+
+    assert_eq!(numbering.pre, vec![Some(0), Some(2), Some(4), None, Some(3), Some(1)]);
+    assert_eq!(numbering.post, vec![Some(4), Some(3), Some(2), None, Some(1), Some(0)]);
+}
+
+// For `dfs::seq` the numbers are the order in which vertices are discovered.
+#[test]
+fn numbering_matches_seq() {
+    let graph: AdjLists = AdjLists::gen_directed(2000, 4000, None);
+    let forest = dfs::seq(&graph);
+    let numbering = forest.numbering(&graph);
+
+    let discovered = forest
+        .iter()
+        .flat_map(|tree| iter::once(tree.root).chain(tree.edges.iter().map(|edge| edge.to)));
+
+    for (i, v) in discovered.enumerate() {
+        assert_eq!(numbering.pre[v], Some(i));
+    }
+
+    assert_eq!(numbering, numbering_seq(&forest, &forest.children()));
+}
+
+// The numbers don't depend on the order of the edges in the trees.
+#[test]
+fn numbering_par() {
+    let graph: AdjLists = AdjLists::gen_undirected(2000, 3000, None);
+    let mut forest = dfs::par(&graph);
+
+    let mut children = forest.children();
+
+    for (v, children) in children.iter_mut().enumerate() {
+        let position = |u: &usize| graph.neighbours(v).position(|w| w == *u);
+        children.sort_by_key(position);
+    }
+
+    let expected = numbering_seq(&forest, &children);
+    assert_eq!(forest.numbering(&graph), expected);
+
+    for tree in &mut forest {
+        tree.edges.reverse();
+    }
+
+    assert_eq!(forest.numbering(&graph), expected);
+}
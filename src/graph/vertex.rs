@@ -0,0 +1,62 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// Integer type used to store vertex ids.
+///
+/// Vertices are always numbered `0..n_verts`. The type only decides how much
+/// memory each id takes, e.g. `u32` is enough for graphs with less than 2^32
+/// vertices and takes half the memory of `usize` on 64-bit platforms.
+///
+/// Algorithms convert ids to `usize` with `index` to use them for indexing
+/// per vertex arrays.
+pub trait VertexId: Copy + Ord + Hash + Debug + Send + Sync + 'static {
+    /// The largest id the type can hold.
+    const MAX: usize;
+
+    /// Convert from an index in `0..=MAX`.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is larger than `MAX`.
+    fn from_index(index: usize) -> Self;
+
+    /// Same as `from_index`, but without the check, for callers which know
+    /// that `index` is in range. A larger index gives a wrong id.
+    fn from_index_unchecked(index: usize) -> Self;
+
+    /// Convert to an index for per vertex arrays.
+    fn index(self) -> usize;
+}
+
+macro_rules! impl_vertex_id {
+    ($T: ident) => {
+        impl VertexId for $T {
+            const MAX: usize = $T::max_value() as usize;
+
+            #[inline]
+            fn from_index(index: usize) -> Self {
+                assert!(
+                    index <= <Self as VertexId>::MAX,
+                    "vertex {} doesn't fit in {}",
+                    index,
+                    stringify!($T)
+                );
+                index as $T
+            }
+
+            #[inline]
+            fn from_index_unchecked(index: usize) -> Self {
+                index as $T
+            }
+
+            #[inline]
+            fn index(self) -> usize {
+                self as usize
+            }
+        }
+    };
+}
+
+impl_vertex_id!(u32);
+impl_vertex_id!(u64);
+impl_vertex_id!(usize);
@@ -0,0 +1,276 @@
+//! Binary graph format
+//!
+//! A little-endian dump of the graph in compressed sparse row form, which
+//! `MappedGraph` uses in place without parsing or copying.
+//!
+//! ```text
+//! magic      8 bytes, "PDFSCSR\0"
+//! version    u32
+//! id bytes   u32, size of a stored vertex id, 4 or 8
+//! n_verts    u64
+//! n_edges    u64
+//! offsets    (n_verts + 1) * u64, neighbours of v are targets[offsets[v]..offsets[v + 1]]
+//! targets    n_edges vertex ids, padded with zeros to a multiple of 8 bytes
+//! checksum   u64, `checksum` of all the words before it
+//! ```
+//!
+//! Every section starts at a multiple of 8 bytes, so a mapped file can be
+//! viewed as slices of integers directly.
+
+use crate::graph::{GraphRef, VertexId};
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::mem;
+use std::ops::Range;
+use std::path::Path;
+
+use rayon::prelude::*;
+
+pub(crate) const MAGIC: [u8; 8] = *b"PDFSCSR\0";
+pub(crate) const VERSION: u32 = 1;
+
+/// Size of the fixed part at the start of the file.
+pub(crate) const HEADER_BYTES: usize = 32;
+
+/// Number of words hashed together before the result is mixed into the
+/// checksum. Blocks are independent, so the checksum can be checked in
+/// parallel.
+const BLOCK_WORDS: usize = 1 << 16;
+
+const PRIME: u64 = 0x100_0000_01b3;
+
+/// Write `graph` in the binary format, storing vertex ids with the size of
+/// `G::Vertex`.
+pub fn write_binary<'a, G, W>(graph: G, out: W) -> io::Result<()>
+where
+    G: GraphRef<'a> + Copy,
+    W: Write,
+{
+    let id_bytes = mem::size_of::<G::Vertex>();
+    let mut out = WordWriter::new(out);
+
+    out.write_bytes(&MAGIC)?;
+    out.write_bytes(&VERSION.to_le_bytes())?;
+    out.write_bytes(&(id_bytes as u32).to_le_bytes())?;
+    out.write_bytes(&(graph.n_verts() as u64).to_le_bytes())?;
+    out.write_bytes(&(graph.n_edges() as u64).to_le_bytes())?;
+
+    let mut offset = 0;
+    out.write_bytes(&0u64.to_le_bytes())?;
+
+    for v in graph.vertices() {
+        offset += graph.out_degree(v) as u64;
+        out.write_bytes(&offset.to_le_bytes())?;
+    }
+
+    for v in graph.vertices() {
+        for to in graph.neighbours(v) {
+            match id_bytes {
+                4 => out.write_bytes(&(to.index() as u32).to_le_bytes())?,
+                _ => out.write_bytes(&(to.index() as u64).to_le_bytes())?,
+            }
+        }
+    }
+
+    out.finish()
+}
+
+/// Whether the file at `path` starts like a file in the binary format.
+pub fn is_binary_file(path: &Path) -> io::Result<bool> {
+    let mut magic = [0; 8];
+
+    match File::open(path)?.read_exact(&mut magic) {
+        Ok(()) => Ok(magic == MAGIC),
+        Err(ref err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// Header of a binary graph file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Header {
+    pub id_bytes: usize,
+    pub n_verts: usize,
+    pub n_edges: usize,
+}
+
+impl Header {
+    /// Read the header from the start of the file `bytes` and check that
+    /// the size of the file matches it.
+    pub fn parse(bytes: &[u8]) -> io::Result<Header> {
+        if bytes.len() < HEADER_BYTES || bytes[..8] != MAGIC {
+            return Err(invalid("not a binary graph file".to_string()));
+        }
+
+        let word = |i: usize| read_u64(&bytes[i..i + 8]);
+        let version = read_u32(&bytes[8..12]);
+
+        if version != VERSION {
+            return Err(invalid(format!(
+                "unsupported version {}, expected {}",
+                version, VERSION
+            )));
+        }
+
+        let header = Header {
+            id_bytes: read_u32(&bytes[12..16]) as usize,
+            n_verts: to_usize(word(16))?,
+            n_edges: to_usize(word(24))?,
+        };
+
+        if header.id_bytes != 4 && header.id_bytes != 8 {
+            return Err(invalid(format!("unsupported id size {}", header.id_bytes)));
+        }
+
+        match header.file_bytes() {
+            Some(len) if len == bytes.len() => Ok(header),
+            _ => Err(invalid(format!(
+                "the file has {} bytes, which doesn't match {} vertices and {} edges",
+                bytes.len(),
+                header.n_verts,
+                header.n_edges
+            ))),
+        }
+    }
+
+    /// Byte range of the offsets.
+    pub fn offsets(&self) -> Range<usize> {
+        HEADER_BYTES..HEADER_BYTES + (self.n_verts + 1) * 8
+    }
+
+    /// Byte range of the targets, without the padding.
+    pub fn targets(&self) -> Range<usize> {
+        let start = self.offsets().end;
+        start..start + self.n_edges * self.id_bytes
+    }
+
+    /// Size of the whole file, `None` if it overflows.
+    fn file_bytes(&self) -> Option<usize> {
+        let offsets = self.n_verts.checked_add(1)?.checked_mul(8)?;
+        let targets = self.n_edges.checked_mul(self.id_bytes)?;
+
+        HEADER_BYTES
+            .checked_add(offsets)?
+            .checked_add(targets)?
+            .checked_add(7)?
+            .checked_add(8)
+            .map(|len| len / 8 * 8)
+    }
+}
+
+/// Checksum of a file's words, not including the checksum itself.
+///
+/// Each block of `BLOCK_WORDS` words is hashed on its own and the block
+/// hashes are combined in order, which lets the blocks be hashed in
+/// parallel.
+pub(crate) fn checksum(words: &[u64]) -> u64 {
+    let blocks = words
+        .par_chunks(BLOCK_WORDS)
+        .map(|block| block.iter().fold(0, |hash, &word| mix(hash, u64::from_le(word))))
+        .collect::<Vec<_>>();
+
+    combine(&blocks, words.len())
+}
+
+fn mix(hash: u64, word: u64) -> u64 {
+    (hash ^ word).wrapping_mul(PRIME).rotate_left(29)
+}
+
+fn combine(blocks: &[u64], n_words: usize) -> u64 {
+    blocks.iter().fold(mix(0, n_words as u64), |hash, &block| mix(hash, block))
+}
+
+/// Writer which groups bytes into little-endian words to compute the
+/// checksum while writing.
+struct WordWriter<W> {
+    out: W,
+    word: [u8; 8],
+    filled: usize,
+    block: u64,
+    block_words: usize,
+    blocks: Vec<u64>,
+    n_words: usize,
+}
+
+impl<W: Write> WordWriter<W> {
+    fn new(out: W) -> Self {
+        WordWriter {
+            out,
+            word: [0; 8],
+            filled: 0,
+            block: 0,
+            block_words: 0,
+            blocks: vec![],
+            n_words: 0,
+        }
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.out.write_all(bytes)?;
+
+        for &byte in bytes {
+            self.word[self.filled] = byte;
+            self.filled += 1;
+
+            if self.filled == 8 {
+                self.end_word();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn end_word(&mut self) {
+        self.block = mix(self.block, read_u64(&self.word));
+        self.block_words += 1;
+        self.n_words += 1;
+        self.filled = 0;
+
+        if self.block_words == BLOCK_WORDS {
+            self.blocks.push(self.block);
+            self.block = 0;
+            self.block_words = 0;
+        }
+    }
+
+    /// Pad to a whole word and write the checksum.
+    fn finish(mut self) -> io::Result<()> {
+        if self.filled > 0 {
+            let padding = [0; 8];
+            let missing = 8 - self.filled;
+            self.write_bytes(&padding[..missing])?;
+        }
+
+        if self.block_words > 0 {
+            self.blocks.push(self.block);
+        }
+
+        let checksum = combine(&self.blocks, self.n_words);
+        self.out.write_all(&checksum.to_le_bytes())?;
+        self.out.flush()
+    }
+}
+
+pub(crate) fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn to_usize(value: u64) -> io::Result<usize> {
+    match value <= usize::max_value() as u64 {
+        true => Ok(value as usize),
+        false => Err(invalid(format!("{} doesn't fit in usize", value))),
+    }
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    let mut word = [0; 4];
+    word.copy_from_slice(bytes);
+    u32::from_le_bytes(word)
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    let mut word = [0; 8];
+    word.copy_from_slice(bytes);
+    u64::from_le_bytes(word)
+}
@@ -0,0 +1,81 @@
+//! DIMACS shortest path format
+//!
+//! ```text
+//! c comment
+//! p sp <n_verts> <n_edges>
+//! a <from> <to> <weight>
+//! ```
+//!
+//! Vertices are counted from 1. Weights are ignored when reading and written
+//! as 1.
+
+use crate::graph::Edge;
+use crate::io::lines::{invalid, parse, parse_vertex, Lines};
+use crate::io::EdgeList;
+
+use std::io::{self, BufRead, Write};
+
+pub(super) const COMMENT: &str = "c";
+
+pub(super) fn read<R: BufRead>(input: R) -> io::Result<EdgeList> {
+    let mut lines = Lines::new(input, COMMENT);
+
+    let (number, line) = lines.expect("problem line")?;
+    let (n_verts, n_edges) = problem(number, &line)?;
+    let mut edges = Vec::new();
+
+    for line in lines {
+        let (number, line) = line?;
+        edges.push(arc(number, &line, n_verts)?);
+    }
+
+    check_count(n_edges, edges.len())?;
+    Ok(EdgeList { n_verts, edges })
+}
+
+/// Parse the problem line, giving the number of vertices and edges.
+pub(super) fn problem(number: usize, line: &str) -> io::Result<(usize, usize)> {
+    let mut tokens = line.split_whitespace();
+
+    if tokens.next() != Some("p") || tokens.next() != Some("sp") {
+        return Err(invalid(number, "expected problem line \"p sp <n> <m>\""));
+    }
+
+    let n_verts = parse(&mut tokens, number, "number of vertices")?;
+    let n_edges = parse(&mut tokens, number, "number of edges")?;
+
+    Ok((n_verts, n_edges))
+}
+
+/// Parse an arc line.
+pub(super) fn arc(number: usize, line: &str, n_verts: usize) -> io::Result<Edge> {
+    let mut tokens = line.split_whitespace();
+
+    if tokens.next() != Some("a") {
+        return Err(invalid(number, "expected arc line \"a <from> <to> <weight>\""));
+    }
+
+    let from = parse_vertex(&mut tokens, number, n_verts)?;
+    let to = parse_vertex(&mut tokens, number, n_verts)?;
+
+    Ok(Edge::new(from, to))
+}
+
+/// Check that the problem line had the right number of arcs.
+pub(super) fn check_count(n_edges: usize, found: usize) -> io::Result<()> {
+    match found == n_edges {
+        true => Ok(()),
+        false => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("expected {} arcs, found {}", n_edges, found),
+        )),
+    }
+}
+
+pub(super) fn write_header<W: Write>(mut out: W, n_verts: usize, n_edges: usize) -> io::Result<()> {
+    writeln!(out, "p sp {} {}", n_verts, n_edges)
+}
+
+pub(super) fn write_edge<W: Write>(mut out: W, from: usize, to: usize) -> io::Result<()> {
+    writeln!(out, "a {} {} 1", from + 1, to + 1)
+}
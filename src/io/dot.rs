@@ -0,0 +1,128 @@
+use crate::graph::{Forest, GraphRef, Tree, VertexId};
+
+use std::io::{self, Write};
+use std::usize;
+
+/// Colors of the tree edges. Each tree gets the next one, repeating when
+/// there are more trees.
+const COLORS: [&str; 8] = [
+    "red", "blue", "green3", "orange", "purple", "cyan3", "magenta", "brown",
+];
+
+/// Write `graph` in the GraphViz DOT format, as a directed graph.
+///
+/// Meant for looking at small graphs, e.g. with `dot -Tsvg`.
+pub fn write_dot<'a, G, W>(graph: G, mut out: W) -> io::Result<()>
+where
+    G: GraphRef<'a> + Copy,
+    W: Write,
+{
+    writeln!(out, "digraph {{")?;
+
+    for v in graph.vertices() {
+        writeln!(out, "    {};", v.index())?;
+    }
+
+    for from in graph.vertices() {
+        for to in graph.neighbours(from) {
+            writeln!(out, "    {} -> {};", from.index(), to.index())?;
+        }
+    }
+
+    writeln!(out, "}}")
+}
+
+/// Write `graph` in the GraphViz DOT format with `forest` drawn over it.
+///
+/// Tree edges are bold and colored by tree, the other edges dashed and roots
+/// have a double circle. With `numbers` each vertex is labeled with its
+/// pre-order number from `Forest::numbering`, which is the order of discovery
+/// for `dfs::seq`.
+pub fn write_forest_dot<'a, G, W>(
+    graph: G,
+    forest: &[Tree<G::Vertex>],
+    numbers: bool,
+    mut out: W,
+) -> io::Result<()>
+where
+    G: GraphRef<'a> + Copy + Sync,
+    W: Write,
+{
+    let parents = forest.parent_array();
+    let mut tree_of = vec![usize::MAX; parents.len()];
+
+    for (i, tree) in forest.iter().enumerate() {
+        tree_of[tree.root.index()] = i;
+
+        for edge in &tree.edges {
+            tree_of[edge.to.index()] = i;
+        }
+    }
+
+    let pre = match numbers {
+        true => forest.numbering(graph).pre,
+        false => Vec::new(),
+    };
+
+    writeln!(out, "digraph {{")?;
+
+    for v in graph.vertices() {
+        let v = v.index();
+        let is_root = tree_of.get(v).map_or(false, |&t| t != usize::MAX) && parents[v].is_none();
+
+        let mut attributes = Vec::new();
+
+        if let Some(Some(number)) = pre.get(v) {
+            attributes.push(format!("label=\"{}\\n#{}\"", v, number));
+        }
+
+        if is_root {
+            attributes.push(String::from("shape=doublecircle"));
+        }
+
+        writeln!(out, "    {}{};", v, format_attributes(&attributes))?;
+    }
+
+    for from in graph.vertices() {
+        for to in graph.neighbours(from) {
+            let is_tree_edge = parents.get(to.index()) == Some(&Some(from));
+
+            let attributes = match is_tree_edge {
+                true => {
+                    let color = COLORS[tree_of[to.index()] % COLORS.len()];
+                    format!(" [color={}, style=bold, penwidth=2]", color)
+                },
+                false => String::from(" [color=gray50, style=dashed]"),
+            };
+
+            writeln!(out, "    {} -> {}{};", from.index(), to.index(), attributes)?;
+        }
+    }
+
+    writeln!(out, "}}")
+}
+
+/// `graph` as a DOT graph, with `forest` drawn over it if there is one.
+///
+/// Shared by the `Graph::write_dot` implementations.
+pub(crate) fn write_graph_dot<'a, G>(
+    graph: G,
+    forest: Option<&[Tree<G::Vertex>]>,
+    numbers: bool,
+    out: &mut dyn Write,
+) -> io::Result<()>
+where
+    G: GraphRef<'a> + Copy + Sync,
+{
+    match forest {
+        Some(forest) => write_forest_dot(graph, forest, numbers, out),
+        None => write_dot(graph, out),
+    }
+}
+
+fn format_attributes(attributes: &[String]) -> String {
+    match attributes.is_empty() {
+        true => String::new(),
+        false => format!(" [{}]", attributes.join(", ")),
+    }
+}
@@ -0,0 +1,114 @@
+use rayon::prelude::*;
+
+use crate::graph::{AdjLists, AdjMatrix, Edge, Graph, Representation};
+
+use std::io;
+
+/// Edges read from a file, before they are put in a graph.
+///
+/// Files may contain loops and multiple edges, which the graphs in this
+/// crate don't allow. `validate` reports them and `remove_invalid` drops
+/// them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EdgeList {
+    pub n_verts: usize,
+    /// Edges in the order they appear in the file, which is also the order
+    /// of the neighbours in the graph.
+    pub edges: Vec<Edge>,
+}
+
+impl EdgeList {
+    /// Check that there are no loops or multiple edges and that all vertices
+    /// are in `0..n_verts`.
+    pub fn validate(&self) -> io::Result<()> {
+        let error = |message| Err(io::Error::new(io::ErrorKind::InvalidData, message));
+
+        if let Some(edge) = self
+            .edges
+            .par_iter()
+            .find_first(|edge| edge.from >= self.n_verts || edge.to >= self.n_verts)
+        {
+            return error(format!("edge {:?} has a vertex out of 0..{}", edge, self.n_verts));
+        }
+
+        if let Some(edge) = self.edges.par_iter().find_first(|edge| edge.from == edge.to) {
+            return error(format!("loops are not allowed, found one at {}", edge.from));
+        }
+
+        let mut sorted = self
+            .edges
+            .par_iter()
+            .map(|edge| (edge.from, edge.to))
+            .collect::<Vec<_>>();
+
+        sorted.par_sort_unstable();
+
+        match sorted.par_windows(2).find_any(|pair| pair[0] == pair[1]) {
+            Some(pair) => error(format!("multiple edges are not allowed, found {:?}", pair[0])),
+            None => Ok(()),
+        }
+    }
+
+    /// Remove loops and all but the first of multiple edges. Returns the
+    /// number of removed edges.
+    pub fn remove_invalid(&mut self) -> usize {
+        let edges = &self.edges;
+
+        let mut order = (0..edges.len())
+            .into_par_iter()
+            .filter(|&i| edges[i].from != edges[i].to)
+            .collect::<Vec<_>>();
+
+        order.par_sort_unstable_by_key(|&i| (edges[i].from, edges[i].to, i));
+
+        // Indices of the edges to keep, in their original order
+        let mut keep = order
+            .par_iter()
+            .enumerate()
+            .filter(|&(j, &i)| j == 0 || edges[order[j - 1]] != edges[i])
+            .map(|(_, &i)| i)
+            .collect::<Vec<_>>();
+
+        keep.par_sort_unstable();
+
+        let removed = edges.len() - keep.len();
+        self.edges = keep.into_par_iter().map(|i| edges[i].clone()).collect();
+
+        removed
+    }
+
+    /// Put the edges in adjacency lists, after checking them with `validate`.
+    pub fn into_adj_lists(self) -> io::Result<AdjLists> {
+        self.validate()?;
+
+        let mut lists = vec![Vec::new(); self.n_verts];
+
+        for edge in self.edges {
+            lists[edge.from].push(edge.to);
+        }
+
+        Ok(AdjLists::from_lists(lists))
+    }
+
+    /// Put the edges in an adjacency matrix, after checking them with
+    /// `validate`.
+    pub fn into_adj_matrix(self) -> io::Result<AdjMatrix> {
+        self.validate()?;
+
+        let mut graph = AdjMatrix::new(self.n_verts);
+
+        for edge in self.edges {
+            graph.add_edge(edge);
+        }
+
+        Ok(graph)
+    }
+
+    /// Put the edges in a graph with the given representation.
+    pub fn into_graph(self, representation: Representation) -> io::Result<Box<dyn Graph>> {
+        Ok(match representation {
+            Representation::Lists => Box::new(self.into_adj_lists()?),
+            Representation::Matrix => Box::new(self.into_adj_matrix()?),
+        })
+    }
+}
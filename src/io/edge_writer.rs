@@ -0,0 +1,81 @@
+use crate::io::{dimacs, matrix_market, snap, Format};
+
+use std::io::{self, Write};
+
+/// Writer of graph files one edge at a time, for graphs which are never
+/// whole in memory.
+///
+/// The number of vertices and edges goes in the header, so it must be known
+/// before the first edge. Edges are written in the order they are given,
+/// which is the order of the neighbours when the file is read.
+///
+/// ```ignore
+/// let mut writer = EdgeWriter::new(Format::Snap, out, 3, 2)?;
+/// writer.edge(0, 1)?;
+/// writer.edge(2, 1)?;
+/// writer.finish()?;
+/// ```
+#[derive(Debug)]
+pub struct EdgeWriter<W: Write> {
+    format: Format,
+    out: W,
+    n_edges: usize,
+    written: usize,
+}
+
+impl<W: Write> EdgeWriter<W> {
+    /// Write the header of a file in `format`.
+    ///
+    /// Fails with `io::ErrorKind::InvalidInput` for formats which aren't
+    /// `Format::is_streamable`.
+    pub fn new(format: Format, mut out: W, n_verts: usize, n_edges: usize) -> io::Result<Self> {
+        if !format.is_streamable() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} files can't be written one edge at a time", format.name()),
+            ));
+        }
+
+        match format {
+            Format::Dimacs => dimacs::write_header(&mut out, n_verts, n_edges)?,
+            Format::MatrixMarket => matrix_market::write_header(&mut out, n_verts, n_edges)?,
+            Format::Snap => snap::write_header(&mut out, n_verts, n_edges)?,
+            Format::Metis => unreachable!(),
+        }
+
+        Ok(EdgeWriter {
+            format,
+            out,
+            n_edges,
+            written: 0,
+        })
+    }
+
+    /// Write the edge *(from, to)*.
+    pub fn edge(&mut self, from: usize, to: usize) -> io::Result<()> {
+        self.written += 1;
+
+        match self.format {
+            Format::Dimacs => dimacs::write_edge(&mut self.out, from, to),
+            Format::MatrixMarket => matrix_market::write_edge(&mut self.out, from, to),
+            Format::Snap => snap::write_edge(&mut self.out, from, to),
+            Format::Metis => unreachable!(),
+        }
+    }
+
+    /// Flush the output and return it.
+    ///
+    /// Fails with `io::ErrorKind::InvalidInput` if the number of edges
+    /// written is not the one in the header.
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.written != self.n_edges {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("wrote {} edges, but the header says {}", self.written, self.n_edges),
+            ));
+        }
+
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
@@ -0,0 +1,154 @@
+use crate::graph::{GraphRef, VertexId};
+use crate::io::lines::{self, Counted};
+use crate::io::{dimacs, matrix_market, metis, snap, EdgeList, EdgeWriter};
+
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Graph file formats which can be read and written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    Dimacs,
+    Metis,
+    MatrixMarket,
+    Snap,
+}
+
+impl Format {
+    pub const ALL: [Format; 4] = [
+        Format::Dimacs,
+        Format::Metis,
+        Format::MatrixMarket,
+        Format::Snap,
+    ];
+
+    /// Short name used on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Dimacs => "dimacs",
+            Format::Metis => "metis",
+            Format::MatrixMarket => "mtx",
+            Format::Snap => "snap",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Format::Dimacs => "DIMACS shortest path, `p sp` and `a` lines",
+            Format::Metis => "METIS adjacency lists of an undirected graph",
+            Format::MatrixMarket => "Matrix Market coordinate pattern matrix",
+            Format::Snap => "SNAP edge list, one `<from> <to>` pair per line",
+        }
+    }
+
+    /// File extensions of the format. The first one is the usual one.
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Format::Dimacs => &["gr", "dimacs"],
+            Format::Metis => &["graph", "metis"],
+            Format::MatrixMarket => &["mtx"],
+            Format::Snap => &["txt", "tsv", "edges", "snap"],
+        }
+    }
+
+    /// Whether files in this format can be written one edge at a time, see
+    /// `EdgeWriter`. METIS can't, each line has all neighbours of a vertex.
+    pub fn is_streamable(self) -> bool {
+        match self {
+            Format::Metis => false,
+            Format::Dimacs | Format::MatrixMarket | Format::Snap => true,
+        }
+    }
+
+    /// Format with the extension of `path`.
+    pub fn from_extension(path: &Path) -> Option<Format> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+
+        Format::ALL
+            .iter()
+            .cloned()
+            .find(|format| format.extensions().contains(&&extension[..]))
+    }
+
+    /// Format recognized from the first line of a file. Only some formats
+    /// have a recognizable header.
+    pub fn from_header(line: &str) -> Option<Format> {
+        let line = line.trim_start();
+        let first = line.split_whitespace().next().unwrap_or("");
+
+        if line.to_lowercase().starts_with(&matrix_market::HEADER.to_lowercase()) {
+            Some(Format::MatrixMarket)
+        } else if first == "c" || first == "p" {
+            Some(Format::Dimacs)
+        } else if line.starts_with('#') {
+            Some(Format::Snap)
+        } else {
+            None
+        }
+    }
+
+    /// Format of a file, from its first line or else its extension.
+    pub fn detect(path: &Path, first_line: &str) -> Option<Format> {
+        Format::from_header(first_line).or_else(|| Format::from_extension(path))
+    }
+
+    /// Read the edges of a graph in this format.
+    ///
+    /// Errors in the input are returned with `io::ErrorKind::InvalidData`,
+    /// and so are more vertices than bytes in the input which no edge uses.
+    /// Loops and multiple edges are not checked, see `EdgeList::validate`.
+    pub fn read<R: BufRead>(self, input: R) -> io::Result<EdgeList> {
+        let mut input = Counted::new(input);
+
+        let list = match self {
+            Format::Dimacs => dimacs::read(&mut input),
+            Format::Metis => metis::read(&mut input),
+            Format::MatrixMarket => matrix_market::read(&mut input),
+            Format::Snap => snap::read(&mut input),
+        }?;
+
+        lines::check_n_verts(list.n_verts, lines::used_verts(&list.edges), input.bytes)?;
+        Ok(list)
+    }
+
+    /// Write `graph` in this format.
+    pub fn write<'a, G, W>(self, graph: G, out: W) -> io::Result<()>
+    where
+        G: GraphRef<'a> + Copy,
+        W: Write,
+    {
+        if let Format::Metis = self {
+            return metis::write(graph, out);
+        }
+
+        let mut writer = EdgeWriter::new(self, out, graph.n_verts(), graph.n_edges())?;
+
+        for from in graph.vertices() {
+            for to in graph.neighbours(from) {
+                writer.edge(from.index(), to.index())?;
+            }
+        }
+
+        writer.finish().map(|_| ())
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Format::ALL
+            .iter()
+            .cloned()
+            .find(|format| format.name() == s)
+            .ok_or_else(|| format!("unknown format {:?}", s))
+    }
+}
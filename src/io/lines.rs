@@ -0,0 +1,163 @@
+use rayon::prelude::*;
+
+use crate::graph::Edge;
+
+use std::fmt::Display;
+use std::io::{self, BufRead, Read};
+use std::str::FromStr;
+
+/// Lines of a text input together with their numbers, starting from 1.
+///
+/// Lines starting with `comment` are skipped, and so are empty lines unless
+/// `keep_empty` is set.
+pub(super) struct Lines<R> {
+    lines: io::Lines<R>,
+    number: usize,
+    comment: &'static str,
+    keep_empty: bool,
+}
+
+impl<R: BufRead> Lines<R> {
+    pub fn new(input: R, comment: &'static str) -> Self {
+        Lines {
+            lines: input.lines(),
+            number: 0,
+            comment,
+            keep_empty: false,
+        }
+    }
+
+    pub fn keep_empty(mut self) -> Self {
+        self.keep_empty = true;
+        self
+    }
+
+    /// Next line, even if it is a comment or empty.
+    pub fn next_raw(&mut self) -> Option<io::Result<(usize, String)>> {
+        let line = self.lines.next()?;
+        self.number += 1;
+
+        Some(line.map(|line| (self.number, line)))
+    }
+
+    /// Next line, or an error if the input ended before `what`.
+    pub fn expect(&mut self, what: &str) -> io::Result<(usize, String)> {
+        match self.next() {
+            Some(line) => line,
+            None => Err(invalid(self.number + 1, format!("expected {}", what))),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Lines<R> {
+    type Item = io::Result<(usize, String)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (number, line) = match self.next_raw()? {
+                Ok(line) => line,
+                Err(err) => return Some(Err(err)),
+            };
+
+            let trimmed = line.trim();
+
+            if trimmed.starts_with(self.comment) || (trimmed.is_empty() && !self.keep_empty) {
+                continue;
+            }
+
+            return Some(Ok((number, line)));
+        }
+    }
+}
+
+/// Error for malformed input at `line`.
+pub(super) fn invalid(line: usize, message: impl Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", line, message))
+}
+
+/// Parse the next token of `line` as `what`.
+pub(super) fn parse<'t, T, I>(tokens: &mut I, line: usize, what: &str) -> io::Result<T>
+where
+    T: FromStr,
+    I: Iterator<Item = &'t str>,
+{
+    let token = tokens
+        .next()
+        .ok_or_else(|| invalid(line, format!("missing {}", what)))?;
+
+    token
+        .parse()
+        .map_err(|_| invalid(line, format!("invalid {} {:?}", what, token)))
+}
+
+/// Parse the next token of `line` as a vertex id counted from 1, and convert
+/// it to one counted from 0.
+pub(super) fn parse_vertex<'t, I>(tokens: &mut I, line: usize, n_verts: usize) -> io::Result<usize>
+where
+    I: Iterator<Item = &'t str>,
+{
+    let v: usize = parse(tokens, line, "vertex")?;
+
+    match v >= 1 && v <= n_verts {
+        true => Ok(v - 1),
+        false => Err(invalid(line, format!("vertex {} is not in 1..={}", v, n_verts))),
+    }
+}
+
+/// Input which counts the bytes read from it.
+pub(super) struct Counted<R> {
+    input: R,
+    pub bytes: usize,
+}
+
+impl<R> Counted<R> {
+    pub fn new(input: R) -> Self {
+        Counted { input, bytes: 0 }
+    }
+}
+
+impl<R: Read> Read for Counted<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.input.read(buf)?;
+        self.bytes += n;
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for Counted<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.input.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.bytes += amt;
+        self.input.consume(amt);
+    }
+}
+
+/// One more than the largest vertex of `edges`.
+pub(super) fn used_verts(edges: &[Edge]) -> usize {
+    edges
+        .par_iter()
+        .map(|edge| edge.from.max(edge.to) + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Check the number of vertices of a file with `bytes` bytes, whose edges
+/// use the vertices below `used`, before the graph is allocated.
+///
+/// The number comes from the header, or a `# Nodes:` comment for SNAP, and
+/// can't be trusted: vertices without edges don't appear in the file, so a
+/// one-line file could ask for any number of them and abort the reader when
+/// they don't fit in memory. More vertices than bytes in the input are
+/// therefore only allowed if the edges use them.
+pub(super) fn check_n_verts(n_verts: usize, used: usize, bytes: usize) -> io::Result<()> {
+    match n_verts <= used || n_verts <= bytes {
+        true => Ok(()),
+        false => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} vertices are too many for a file of {} bytes", n_verts, bytes),
+        )),
+    }
+}
@@ -0,0 +1,128 @@
+//! Matrix Market coordinate format
+//!
+//! ```text
+//! %%MatrixMarket matrix coordinate pattern general
+//! % comment
+//! <rows> <columns> <entries>
+//! <row> <column>
+//! ```
+//!
+//! The graph is the adjacency matrix, so it must be square. Vertices are
+//! counted from 1. `real` and `integer` matrices are read too, ignoring the
+//! values, and `symmetric` ones get the entries in both directions.
+
+use crate::graph::Edge;
+use crate::io::lines::{invalid, parse, parse_vertex, Lines};
+use crate::io::EdgeList;
+
+use std::io::{self, BufRead, Write};
+
+pub(super) const HEADER: &str = "%%MatrixMarket";
+
+pub(super) const COMMENT: &str = "%";
+
+pub(super) fn read<R: BufRead>(input: R) -> io::Result<EdgeList> {
+    let mut lines = Lines::new(input, COMMENT);
+
+    let (number, line) = match lines.next_raw() {
+        Some(line) => line?,
+        None => return Err(invalid(1, "expected header")),
+    };
+
+    let symmetric = banner(number, &line)?;
+
+    let (number, line) = lines.expect("size line")?;
+    let (n_verts, entries) = size(number, &line)?;
+
+    let mut edges = Vec::new();
+    let mut found = 0;
+
+    for line in lines {
+        let (number, line) = line?;
+        entry(number, &line, n_verts, symmetric, &mut edges)?;
+        found += 1;
+    }
+
+    check_count(entries, found)?;
+    Ok(EdgeList { n_verts, edges })
+}
+
+/// Parse the header line, giving whether the matrix is symmetric.
+pub(super) fn banner(number: usize, line: &str) -> io::Result<bool> {
+    let header = line.to_lowercase();
+    let mut tokens = header.split_whitespace();
+
+    if tokens.next() != Some(&HEADER.to_lowercase()[..]) {
+        return Err(invalid(number, format!("expected header starting with {}", HEADER)));
+    }
+
+    match (tokens.next(), tokens.next(), tokens.next(), tokens.next()) {
+        (Some("matrix"), Some("coordinate"), Some(field), Some(symmetry))
+            if field == "pattern" || field == "real" || field == "integer" =>
+        {
+            match symmetry {
+                "general" => Ok(false),
+                "symmetric" => Ok(true),
+                _ => Err(invalid(number, format!("unsupported symmetry {:?}", symmetry))),
+            }
+        },
+        _ => Err(invalid(number, "only coordinate pattern matrices are supported")),
+    }
+}
+
+/// Parse the size line, giving the number of vertices and entries.
+pub(super) fn size(number: usize, line: &str) -> io::Result<(usize, usize)> {
+    let mut tokens = line.split_whitespace();
+
+    let rows: usize = parse(&mut tokens, number, "number of rows")?;
+    let columns: usize = parse(&mut tokens, number, "number of columns")?;
+    let entries: usize = parse(&mut tokens, number, "number of entries")?;
+
+    match rows == columns {
+        true => Ok((rows, entries)),
+        false => Err(invalid(number, format!("the matrix is {}x{}, not square", rows, columns))),
+    }
+}
+
+/// Parse an entry and add its edges, two of them if the matrix is
+/// symmetric.
+pub(super) fn entry(
+    number: usize,
+    line: &str,
+    n_verts: usize,
+    symmetric: bool,
+    edges: &mut Vec<Edge>,
+) -> io::Result<()> {
+    let mut tokens = line.split_whitespace();
+
+    let from = parse_vertex(&mut tokens, number, n_verts)?;
+    let to = parse_vertex(&mut tokens, number, n_verts)?;
+
+    edges.push(Edge::new(from, to));
+
+    if symmetric && from != to {
+        edges.push(Edge::new(to, from));
+    }
+
+    Ok(())
+}
+
+/// Check that the size line had the right number of entries.
+pub(super) fn check_count(entries: usize, found: usize) -> io::Result<()> {
+    match found == entries {
+        true => Ok(()),
+        false => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("expected {} entries, found {}", entries, found),
+        )),
+    }
+}
+
+pub(super) fn write_header<W: Write>(mut out: W, n_verts: usize, n_edges: usize) -> io::Result<()> {
+    writeln!(out, "{} matrix coordinate pattern general", HEADER)?;
+    writeln!(out, "{} {} {}", n_verts, n_verts, n_edges)
+}
+
+pub(super) fn write_edge<W: Write>(mut out: W, from: usize, to: usize) -> io::Result<()> {
+    writeln!(out, "{} {}", from + 1, to + 1)
+}
@@ -0,0 +1,166 @@
+//! METIS graph format
+//!
+//! ```text
+//! % comment
+//! <n_verts> <n_edges> [<fmt> [<ncon>]]
+//! <neighbours of vertex 1>
+//! <neighbours of vertex 2>
+//! ...
+//! ```
+//!
+//! Vertices are counted from 1. The graph is undirected, every edge is listed
+//! by both of its vertices and counted once in the header. Vertex sizes,
+//! vertex weights and edge weights, as given by `fmt`, are skipped.
+
+use crate::graph::{Edge, GraphRef, VertexId};
+use crate::io::lines::{invalid, parse, parse_vertex, Lines};
+use crate::io::EdgeList;
+
+use std::io::{self, BufRead, Write};
+
+pub(super) const COMMENT: &str = "%";
+
+/// Header line of a METIS file.
+pub(super) struct Header {
+    pub n_verts: usize,
+    pub n_edges: usize,
+    /// Number of vertex sizes and weights at the start of each line.
+    skip: usize,
+    edge_weights: bool,
+}
+
+pub(super) fn read<R: BufRead>(input: R) -> io::Result<EdgeList> {
+    let mut lines = Lines::new(input, COMMENT);
+
+    let (number, line) = lines.expect("header line")?;
+    let header = header(number, &line)?;
+
+    // Empty lines are vertices without neighbours
+    let mut lines = lines.keep_empty();
+    let mut edges = Vec::new();
+
+    for from in 0..header.n_verts {
+        let (number, line) = lines.expect(&format!("neighbours of vertex {}", from + 1))?;
+        neighbours(&header, number, &line, from, &mut edges)?;
+    }
+
+    for line in lines {
+        let (number, line) = line?;
+        neighbours(&header, number, &line, header.n_verts, &mut edges)?;
+    }
+
+    check_count(&header, edges.len())?;
+
+    Ok(EdgeList {
+        n_verts: header.n_verts,
+        edges,
+    })
+}
+
+pub(super) fn header(number: usize, line: &str) -> io::Result<Header> {
+    let mut tokens = line.split_whitespace();
+
+    let n_verts = parse(&mut tokens, number, "number of vertices")?;
+    let n_edges = parse(&mut tokens, number, "number of edges")?;
+
+    let fmt = tokens.next().unwrap_or("0");
+    let ncon = match tokens.clone().next() {
+        Some(_) => parse(&mut tokens, number, "ncon")?,
+        None => 1,
+    };
+
+    if fmt.len() > 3 || fmt.chars().any(|c| c != '0' && c != '1') {
+        return Err(invalid(number, format!("invalid fmt {:?}", fmt)));
+    }
+
+    let fmt = format!("{:0>3}", fmt).into_bytes();
+    let (sizes, vertex_weights, edge_weights) = (fmt[0] == b'1', fmt[1] == b'1', fmt[2] == b'1');
+
+    Ok(Header {
+        n_verts,
+        n_edges,
+        skip: sizes as usize + vertex_weights as usize * ncon,
+        edge_weights,
+    })
+}
+
+/// Parse the line of vertex `from` and add its edges. Lines after the last
+/// vertex, i.e. with `from == n_verts`, must be empty.
+pub(super) fn neighbours(
+    header: &Header,
+    number: usize,
+    line: &str,
+    from: usize,
+    edges: &mut Vec<Edge>,
+) -> io::Result<()> {
+    if from >= header.n_verts {
+        return match line.trim().is_empty() {
+            true => Ok(()),
+            false => Err(invalid(number, format!("expected only {} vertex lines", header.n_verts))),
+        };
+    }
+
+    let mut tokens = line.split_whitespace().peekable();
+
+    for _ in 0..header.skip {
+        parse::<u64, _>(&mut tokens, number, "vertex weight")?;
+    }
+
+    while tokens.peek().is_some() {
+        let to = parse_vertex(&mut tokens, number, header.n_verts)?;
+        edges.push(Edge::new(from, to));
+
+        if header.edge_weights {
+            parse::<u64, _>(&mut tokens, number, "edge weight")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that every edge of the header was listed twice.
+pub(super) fn check_count(header: &Header, found: usize) -> io::Result<()> {
+    match found == 2 * header.n_edges {
+        true => Ok(()),
+        false => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "expected {} edges listed twice, found {} neighbours",
+                header.n_edges, found
+            ),
+        )),
+    }
+}
+
+/// Fails with `InvalidInput` if the graph is not undirected.
+pub(super) fn write<'a, G, W>(graph: G, mut out: W) -> io::Result<()>
+where
+    G: GraphRef<'a> + Copy,
+    W: Write,
+{
+    let undirected = graph
+        .vertices()
+        .all(|from| graph.neighbours(from).all(|to| graph.has_edge(to, from)));
+
+    if !undirected {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "METIS graphs must be undirected",
+        ));
+    }
+
+    writeln!(out, "{} {}", graph.n_verts(), graph.n_edges() / 2)?;
+
+    for from in graph.vertices() {
+        let mut separator = "";
+
+        for to in graph.neighbours(from) {
+            write!(out, "{}{}", separator, to.index() + 1)?;
+            separator = " ";
+        }
+
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
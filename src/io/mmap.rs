@@ -0,0 +1,98 @@
+//! Read-only file mappings
+//!
+//! Files are mapped with `mmap` on unix. Elsewhere they are read into
+//! memory, which gives the same interface without the fast start.
+
+use std::fs::File;
+use std::io;
+use std::mem;
+use std::slice;
+
+/// Contents of a whole file, aligned to 8 bytes.
+pub(crate) struct Mapping {
+    ptr: *const u64,
+    len: usize,
+    /// Words of the file when it isn't mapped.
+    #[cfg_attr(unix, allow(dead_code))]
+    owned: Vec<u64>,
+}
+
+// The mapping is read-only and owned by the value, like a `Box<[u64]>`.
+unsafe impl Send for Mapping {}
+unsafe impl Sync for Mapping {}
+
+impl Mapping {
+    /// Map all of `file`, which must not be empty.
+    pub fn new(file: &File) -> io::Result<Mapping> {
+        let len = file.metadata()?.len();
+
+        if len == 0 || len > usize::max_value() as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("can't map a file of {} bytes", len),
+            ));
+        }
+
+        Mapping::map(file, len as usize)
+    }
+
+    #[cfg(unix)]
+    fn map(file: &File, len: usize) -> io::Result<Mapping> {
+        use std::os::unix::io::AsRawFd;
+        use std::ptr;
+
+        let ptr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+
+        match ptr == libc::MAP_FAILED {
+            true => Err(io::Error::last_os_error()),
+            false => Ok(Mapping {
+                ptr: ptr as *const u64,
+                len,
+                owned: vec![],
+            }),
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn map(mut file: &File, len: usize) -> io::Result<Mapping> {
+        use std::io::Read;
+
+        let mut owned = vec![0u64; (len + 7) / 8];
+        let bytes = unsafe { slice::from_raw_parts_mut(owned.as_mut_ptr() as *mut u8, len) };
+        file.read_exact(bytes)?;
+
+        Ok(Mapping {
+            ptr: owned.as_ptr(),
+            len,
+            owned,
+        })
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.ptr as *const u8, self.len) }
+    }
+
+    /// The whole words of the file. Bytes after the last multiple of 8 are
+    /// left out.
+    pub fn words(&self) -> &[u64] {
+        unsafe { slice::from_raw_parts(self.ptr, self.len / mem::size_of::<u64>()) }
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        unsafe {
+            libc::munmap(self.ptr as *mut libc::c_void, self.len);
+        }
+    }
+}
@@ -0,0 +1,64 @@
+//! Reading and writing graphs and forests
+
+pub(crate) mod binary;
+mod dimacs;
+mod dot;
+mod edge_list;
+mod edge_writer;
+mod format;
+mod lines;
+mod matrix_market;
+mod metis;
+mod mmap;
+mod parallel;
+mod snap;
+
+pub use self::binary::{is_binary_file, write_binary};
+pub use self::dot::{write_dot, write_forest_dot};
+pub use self::edge_list::EdgeList;
+pub use self::edge_writer::EdgeWriter;
+pub use self::format::Format;
+pub use self::parallel::{parse_par, read_file_par, ParseStats};
+pub(crate) use self::dot::write_graph_dot;
+pub(crate) use self::mmap::Mapping;
+
+use crate::graph::GraphRef;
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Cursor, Read, Write};
+use std::path::Path;
+
+/// Read the edges of a graph from a file, detecting the format from the
+/// first line or the extension.
+///
+/// ```ignore
+/// let graph = io::read_file("roadNet-CA.txt".as_ref())?.into_adj_lists()?;
+/// ```
+pub fn read_file(path: &Path) -> io::Result<EdgeList> {
+    let mut input = BufReader::new(File::open(path)?);
+    let mut first_line = String::new();
+    input.read_line(&mut first_line)?;
+
+    let format = Format::detect(path, &first_line).ok_or_else(|| unknown_format(path))?;
+
+    format.read(Cursor::new(first_line.into_bytes()).chain(input))
+}
+
+/// Write `graph` to a file in the format given by the extension.
+pub fn write_file<'a, G: GraphRef<'a> + Copy>(graph: G, path: &Path) -> io::Result<()> {
+    let format = Format::from_extension(path).ok_or_else(|| unknown_format(path))?;
+
+    let mut out = BufWriter::new(File::create(path)?);
+    format.write(graph, &mut out)?;
+    out.flush()
+}
+
+fn unknown_format(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("can't tell the format of {}", path.display()),
+    )
+}
+
+#[cfg(test)]
+mod tests;
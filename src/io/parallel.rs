@@ -0,0 +1,506 @@
+//! Parallel parsing of text graph files
+//!
+//! The header is read sequentially, then the rest of the input is split into
+//! chunks at line boundaries which are parsed with `rayon`. The edges of all
+//! chunks are then placed into the adjacency lists with a parallel counting
+//! sort.
+
+use rayon::prelude::*;
+
+use crate::graph::{AdjLists, Edge};
+use crate::io::lines::{self, invalid};
+use crate::io::{dimacs, matrix_market, metis, snap, unknown_format, Format, Mapping};
+use crate::progress::{self, Phase};
+
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::str;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Size of the chunks the input is split into. A chunk is a bit longer, up
+/// to the end of its last line. Tests use small chunks to have many of them.
+#[cfg(not(test))]
+const CHUNK_BYTES: usize = 1 << 20;
+#[cfg(test)]
+const CHUNK_BYTES: usize = 1 << 12;
+
+/// Statistics of a parallel parse.
+#[derive(Debug, Clone)]
+pub struct ParseStats {
+    pub bytes: usize,
+    pub lines: usize,
+    /// Edges in the graph, after removing invalid ones.
+    pub edges: usize,
+    /// Loops and multiple edges which were removed.
+    pub removed: usize,
+    pub chunks: usize,
+    pub elapsed: Duration,
+}
+
+impl ParseStats {
+    /// Megabytes of input parsed per second.
+    pub fn megabytes_per_sec(&self) -> f64 {
+        self.bytes as f64 / 1e6 / seconds(self.elapsed)
+    }
+
+    /// Millions of edges parsed per second.
+    pub fn medges_per_sec(&self) -> f64 {
+        (self.edges + self.removed) as f64 / 1e6 / seconds(self.elapsed)
+    }
+}
+
+impl fmt::Display for ParseStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:?} ({} lines, {} edges, {:.1} MB/s, {:.2} M edges/s, {} chunks)",
+            self.elapsed,
+            self.lines,
+            self.edges,
+            self.megabytes_per_sec(),
+            self.medges_per_sec(),
+            self.chunks,
+        )?;
+
+        if self.removed > 0 {
+            write!(f, ", removed {} invalid edges", self.removed)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse a text graph in `format` in parallel.
+///
+/// Accepts the same input as `Format::read`, but the neighbours of each
+/// vertex are sorted instead of in the order of the file. Loops and multiple
+/// edges are an error, or removed if `remove_invalid` is set.
+pub fn parse_par(
+    input: &[u8],
+    format: Format,
+    remove_invalid: bool,
+) -> io::Result<(AdjLists, ParseStats)> {
+    let start = Instant::now();
+    progress::phase(Phase::Parse);
+
+    let header = Header::parse(input, format)?;
+    let chunks = split(&input[header.body..]);
+
+    let counts = chunks
+        .par_iter()
+        .map(|chunk| Counts::of(chunk, &header))
+        .collect::<Vec<_>>();
+
+    // Number of the first line and the first METIS vertex of every chunk
+    let mut firsts = Vec::with_capacity(chunks.len());
+    let (mut line, mut vertex) = (header.lines + 1, 0);
+
+    for count in &counts {
+        firsts.push((line, vertex));
+        line += count.lines;
+        vertex += count.vertices;
+    }
+
+    let parsed = chunks
+        .par_iter()
+        .zip(counts.par_iter())
+        .zip(firsts)
+        .map(|((chunk, counts), (line, vertex))| parse_chunk(chunk, counts, &header, line, vertex))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let found = parsed.iter().map(|chunk| chunk.entries).sum::<usize>();
+    let n_edges = parsed.iter().map(|chunk| chunk.edges.len()).sum::<usize>();
+
+    let n_verts = match header.kind {
+        Kind::Dimacs { n_edges } => {
+            dimacs::check_count(n_edges, found)?;
+            header.n_verts
+        },
+        Kind::Metis(ref metis) => {
+            if vertex < metis.n_verts {
+                let message = format!("expected neighbours of vertex {}", vertex + 1);
+                return Err(invalid(line, message));
+            }
+
+            metis::check_count(metis, n_edges)?;
+            header.n_verts
+        },
+        Kind::MatrixMarket { entries, .. } => {
+            matrix_market::check_count(entries, found)?;
+            header.n_verts
+        },
+        Kind::Snap => parsed.iter().map(|chunk| chunk.n_verts).max().unwrap_or(0),
+    };
+
+    let used = parsed
+        .par_iter()
+        .map(|chunk| lines::used_verts(&chunk.edges))
+        .max()
+        .unwrap_or(0);
+    lines::check_n_verts(n_verts, used, input.len())?;
+
+    let (lists, removed) = assemble(n_verts, &parsed, remove_invalid)?;
+
+    let stats = ParseStats {
+        bytes: input.len(),
+        lines: line - 1,
+        edges: n_edges - removed,
+        removed,
+        chunks: chunks.len(),
+        elapsed: Instant::now().duration_since(start),
+    };
+
+    Ok((AdjLists::from_lists(lists), stats))
+}
+
+/// Parse a text graph file in parallel, detecting the format like
+/// `io::read_file`. The file is memory-mapped rather than read.
+pub fn read_file_par(path: &Path, remove_invalid: bool) -> io::Result<(AdjLists, ParseStats)> {
+    let file = File::open(path)?;
+
+    let mapping = match file.metadata()?.len() {
+        0 => None,
+        _ => Some(Mapping::new(&file)?),
+    };
+
+    let input = mapping.as_ref().map_or(&[][..], |mapping| mapping.bytes());
+    let first_line = input.split(|&b| b == b'\n').next().unwrap_or(&[]);
+
+    let format = Format::detect(path, &String::from_utf8_lossy(first_line))
+        .ok_or_else(|| unknown_format(path))?;
+
+    parse_par(input, format, remove_invalid)
+}
+
+/// What was read before the edges.
+struct Header {
+    kind: Kind,
+    n_verts: usize,
+    /// Position of the first byte after the header.
+    body: usize,
+    /// Number of lines in the header.
+    lines: usize,
+}
+
+enum Kind {
+    Dimacs { n_edges: usize },
+    Metis(metis::Header),
+    MatrixMarket { symmetric: bool, entries: usize },
+    Snap,
+}
+
+impl Header {
+    fn parse(input: &[u8], format: Format) -> io::Result<Header> {
+        let mut lines = HeaderLines {
+            input,
+            body: 0,
+            number: 0,
+        };
+
+        let (kind, n_verts) = match format {
+            Format::Dimacs => {
+                let (number, line) = lines.expect(dimacs::COMMENT, "problem line")?;
+                let (n_verts, n_edges) = dimacs::problem(number, line)?;
+
+                (Kind::Dimacs { n_edges }, n_verts)
+            },
+            Format::Metis => {
+                let (number, line) = lines.expect(metis::COMMENT, "header line")?;
+                let header = metis::header(number, line)?;
+                let n_verts = header.n_verts;
+
+                (Kind::Metis(header), n_verts)
+            },
+            Format::MatrixMarket => {
+                let (number, line) = lines.next().unwrap_or((1, ""));
+                let symmetric = matrix_market::banner(number, line)?;
+
+                let (number, line) = lines.expect(matrix_market::COMMENT, "size line")?;
+                let (n_verts, entries) = matrix_market::size(number, line)?;
+
+                (Kind::MatrixMarket { symmetric, entries }, n_verts)
+            },
+            Format::Snap => (Kind::Snap, 0),
+        };
+
+        Ok(Header {
+            kind,
+            n_verts,
+            body: lines.body,
+            lines: lines.number,
+        })
+    }
+
+    fn comment(&self) -> &'static str {
+        match self.kind {
+            Kind::Dimacs { .. } => dimacs::COMMENT,
+            Kind::Metis(_) => metis::COMMENT,
+            Kind::MatrixMarket { .. } => matrix_market::COMMENT,
+            Kind::Snap => snap::COMMENT,
+        }
+    }
+}
+
+/// Lines at the start of the input, like `Lines`.
+struct HeaderLines<'a> {
+    input: &'a [u8],
+    body: usize,
+    number: usize,
+}
+
+impl<'a> HeaderLines<'a> {
+    /// Next line, even if it is a comment or empty.
+    fn next(&mut self) -> Option<(usize, &'a str)> {
+        let rest = &self.input[self.body..];
+
+        if rest.is_empty() {
+            return None;
+        }
+
+        let len = rest.iter().position(|&b| b == b'\n').map_or(rest.len(), |i| i + 1);
+        self.body += len;
+        self.number += 1;
+
+        // Errors are reported by the caller, which finds an invalid header
+        Some((self.number, str::from_utf8(&rest[..len]).unwrap_or("")))
+    }
+
+    /// Next line which isn't a comment or empty.
+    fn expect(&mut self, comment: &str, what: &str) -> io::Result<(usize, &'a str)> {
+        while let Some((number, line)) = self.next() {
+            let trimmed = line.trim();
+
+            if !trimmed.is_empty() && !trimmed.starts_with(comment) {
+                return Ok((number, line));
+            }
+        }
+
+        Err(invalid(self.number + 1, format!("expected {}", what)))
+    }
+}
+
+/// Split `input` into chunks of about `CHUNK_BYTES` which end at the end
+/// of a line.
+fn split(input: &[u8]) -> Vec<&[u8]> {
+    let mut chunks = vec![];
+    let mut rest = input;
+
+    while !rest.is_empty() {
+        let len = match rest.len() > CHUNK_BYTES {
+            true => rest[CHUNK_BYTES..]
+                .iter()
+                .position(|&b| b == b'\n')
+                .map_or(rest.len(), |i| CHUNK_BYTES + i + 1),
+            false => rest.len(),
+        };
+
+        let (chunk, after) = rest.split_at(len);
+        chunks.push(chunk);
+        rest = after;
+    }
+
+    chunks
+}
+
+/// Lines of a chunk.
+struct Counts {
+    lines: usize,
+    /// Vertex lines of a METIS file, i.e. those which aren't comments.
+    vertices: usize,
+}
+
+impl Counts {
+    fn of(chunk: &[u8], header: &Header) -> Counts {
+        let lines = chunk.iter().filter(|&&b| b == b'\n').count()
+            + (chunk.last() != Some(&b'\n')) as usize;
+
+        let vertices = match header.kind {
+            Kind::Metis(_) => lines_of(chunk, lines)
+                .filter(|line| !trim_start(line).starts_with(metis::COMMENT.as_bytes()))
+                .count(),
+            _ => 0,
+        };
+
+        Counts { lines, vertices }
+    }
+}
+
+/// Edges of a chunk.
+#[derive(Default)]
+struct Parsed {
+    edges: Vec<Edge>,
+    /// Lines with edges, for formats which give their number in the header.
+    entries: usize,
+    /// One more than the largest vertex of a SNAP file.
+    n_verts: usize,
+}
+
+fn parse_chunk(
+    chunk: &[u8],
+    counts: &Counts,
+    header: &Header,
+    first_line: usize,
+    first_vertex: usize,
+) -> io::Result<Parsed> {
+    let mut parsed = Parsed::default();
+    let mut vertex = first_vertex;
+    let comment = header.comment();
+
+    for (i, line) in lines_of(chunk, counts.lines).enumerate() {
+        let number = first_line + i;
+        let line = str::from_utf8(line).map_err(|_| invalid(number, "invalid UTF-8"))?;
+        let trimmed = line.trim();
+
+        if trimmed.starts_with(comment) {
+            if let Kind::Snap = header.kind {
+                let nodes = snap::nodes(trimmed, number)?.unwrap_or(0);
+                parsed.n_verts = parsed.n_verts.max(nodes);
+            }
+
+            continue;
+        }
+
+        match header.kind {
+            // Empty lines are vertices without neighbours
+            Kind::Metis(ref metis) => {
+                let from = vertex.min(metis.n_verts);
+                metis::neighbours(metis, number, line, from, &mut parsed.edges)?;
+                vertex += 1;
+                continue;
+            },
+            _ if trimmed.is_empty() => continue,
+            Kind::Dimacs { .. } => {
+                parsed.edges.push(dimacs::arc(number, line, header.n_verts)?);
+            },
+            Kind::MatrixMarket { symmetric, .. } => {
+                matrix_market::entry(number, line, header.n_verts, symmetric, &mut parsed.edges)?;
+            },
+            Kind::Snap => {
+                let edge = snap::edge(number, trimmed)?;
+                parsed.n_verts = parsed.n_verts.max(edge.from + 1).max(edge.to + 1);
+                parsed.edges.push(edge);
+            },
+        }
+
+        parsed.entries += 1;
+    }
+
+    Ok(parsed)
+}
+
+/// Put the edges into adjacency lists and sort them. Returns the lists and
+/// the number of removed edges.
+///
+/// The edges are placed with a counting sort: the out-degrees give the
+/// start of each vertex's targets in one array, and every edge is written
+/// at the next free position of its source vertex.
+fn assemble(
+    n_verts: usize,
+    parsed: &[Parsed],
+    remove_invalid: bool,
+) -> io::Result<(Vec<Vec<usize>>, usize)> {
+    let degrees = atomic_vec(n_verts);
+
+    parsed.par_iter().for_each(|chunk| {
+        for edge in &chunk.edges {
+            degrees[edge.from].fetch_add(1, Ordering::Relaxed);
+        }
+    });
+
+    let mut offsets = Vec::with_capacity(n_verts + 1);
+    let mut offset = 0;
+    offsets.push(offset);
+
+    for degree in &degrees {
+        offset += degree.load(Ordering::Relaxed);
+        offsets.push(offset);
+    }
+
+    // The degrees become the next free position of each vertex
+    degrees
+        .par_iter()
+        .zip(&offsets)
+        .for_each(|(next, &offset)| next.store(offset, Ordering::Relaxed));
+
+    let targets = atomic_vec(offset);
+
+    parsed.par_iter().for_each(|chunk| {
+        for edge in &chunk.edges {
+            let i = degrees[edge.from].fetch_add(1, Ordering::Relaxed);
+            targets[i].store(edge.to, Ordering::Relaxed);
+        }
+    });
+
+    drop(degrees);
+
+    let mut lists = offsets
+        .par_windows(2)
+        .map(|range| {
+            let mut list = targets[range[0]..range[1]]
+                .iter()
+                .map(|v| v.load(Ordering::Relaxed))
+                .collect::<Vec<_>>();
+
+            list.sort_unstable();
+            list
+        })
+        .collect::<Vec<_>>();
+
+    if remove_invalid {
+        let removed = lists
+            .par_iter_mut()
+            .enumerate()
+            .map(|(u, list)| {
+                let len = list.len();
+                list.dedup();
+                list.retain(|&v| v != u);
+                len - list.len()
+            })
+            .sum();
+
+        return Ok((lists, removed));
+    }
+
+    let error = |message| Err(io::Error::new(io::ErrorKind::InvalidData, message));
+
+    let has_loop = |&u: &usize| lists[u].binary_search(&u).is_ok();
+
+    if let Some(u) = (0..n_verts).into_par_iter().find_first(has_loop) {
+        return error(format!("loops are not allowed, found one at {}", u));
+    }
+
+    let multiple = lists
+        .par_iter()
+        .enumerate()
+        .filter_map(|(u, list)| {
+            let pair = list.windows(2).find(|pair| pair[0] == pair[1])?;
+            Some((u, pair[0]))
+        })
+        .find_first(|_| true);
+
+    match multiple {
+        Some(edge) => error(format!("multiple edges are not allowed, found {:?}", edge)),
+        None => Ok((lists, 0)),
+    }
+}
+
+fn atomic_vec(len: usize) -> Vec<AtomicUsize> {
+    (0..len).into_par_iter().map(|_| AtomicUsize::new(0)).collect()
+}
+
+/// The first `n` lines of `chunk`, without the line endings.
+fn lines_of(chunk: &[u8], n: usize) -> impl Iterator<Item = &[u8]> {
+    chunk.split(|&b| b == b'\n').take(n)
+}
+
+fn trim_start(line: &[u8]) -> &[u8] {
+    let start = line.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(line.len());
+    &line[start..]
+}
+
+fn seconds(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) * 1e-9
+}
@@ -0,0 +1,76 @@
+//! SNAP edge lists
+//!
+//! ```text
+//! # Nodes: <n_verts> Edges: <n_edges>
+//! <from> <to>
+//! ```
+//!
+//! One edge per line, separated by a tab or spaces. Vertices are counted
+//! from 0. There are as many vertices as the `Nodes` comment says, or one
+//! more than the largest id if that is more.
+
+use crate::graph::Edge;
+use crate::io::lines::{invalid, parse};
+use crate::io::EdgeList;
+
+use std::io::{self, BufRead, Write};
+
+pub(super) const COMMENT: &str = "#";
+
+pub(super) fn read<R: BufRead>(input: R) -> io::Result<EdgeList> {
+    let mut n_verts = 0;
+    let mut edges = Vec::new();
+
+    for (i, line) in input.lines().enumerate() {
+        let (number, line) = (i + 1, line?);
+        let line = line.trim();
+
+        if line.starts_with(COMMENT) {
+            n_verts = n_verts.max(nodes(line, number)?.unwrap_or(0));
+            continue;
+        }
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let edge = edge(number, line)?;
+        n_verts = n_verts.max(edge.from + 1).max(edge.to + 1);
+        edges.push(edge);
+    }
+
+    Ok(EdgeList { n_verts, edges })
+}
+
+/// Number of vertices from a `# Nodes: <n> Edges: <m>` comment.
+pub(super) fn nodes(comment: &str, number: usize) -> io::Result<Option<usize>> {
+    let mut tokens = comment[1..].split_whitespace();
+
+    while let Some(token) = tokens.next() {
+        if token == "Nodes:" {
+            return parse(&mut tokens, number, "number of nodes").map(Some);
+        }
+    }
+
+    Ok(None)
+}
+
+/// Parse an edge line.
+pub(super) fn edge(number: usize, line: &str) -> io::Result<Edge> {
+    let mut tokens = line.split_whitespace();
+    let from = parse(&mut tokens, number, "vertex")?;
+    let to = parse(&mut tokens, number, "vertex")?;
+
+    match tokens.next() {
+        Some(_) => Err(invalid(number, "expected two vertices")),
+        None => Ok(Edge::new(from, to)),
+    }
+}
+
+pub(super) fn write_header<W: Write>(mut out: W, n_verts: usize, n_edges: usize) -> io::Result<()> {
+    writeln!(out, "# Nodes: {} Edges: {}", n_verts, n_edges)
+}
+
+pub(super) fn write_edge<W: Write>(mut out: W, from: usize, to: usize) -> io::Result<()> {
+    writeln!(out, "{}\t{}", from, to)
+}
@@ -0,0 +1,379 @@
+use crate::dfs;
+use crate::graph::{AdjLists, AdjMatrix, Edge, Graph, GraphRef};
+use super::*;
+
+use std::env;
+use std::path::PathBuf;
+
+fn small() -> AdjLists {
+    let mut graph = AdjLists::new(3);
+    graph.add_edge(Edge::new(0, 1));
+    graph.add_edge(Edge::new(1, 2));
+    graph.add_edge(Edge::new(0, 2));
+    graph
+}
+
+fn to_string(write: impl FnOnce(&mut Vec<u8>)) -> String {
+    let mut out = Vec::new();
+    write(&mut out);
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn dot() {
+    let graph = small();
+    let dot = to_string(|out| write_dot(&graph, out).unwrap());
+
+    assert_eq!(
+        dot,
+        "digraph {\n    0;\n    1;\n    2;\n    0 -> 1;\n    0 -> 2;\n    1 -> 2;\n}\n"
+    );
+}
+
+#[test]
+fn forest_dot() {
+    let graph = small();
+    let forest = dfs::seq(&graph);
+    let dot = to_string(|out| write_forest_dot(&graph, &forest, true, out).unwrap());
+
+    assert!(dot.contains("    0 [label=\"0\\n#0\", shape=doublecircle];\n"));
+    assert!(dot.contains("    2 [label=\"2\\n#2\"];\n"));
+    assert!(dot.contains("    0 -> 1 [color=red, style=bold, penwidth=2];\n"));
+    assert!(dot.contains("    1 -> 2 [color=red, style=bold, penwidth=2];\n"));
+    assert!(dot.contains("    0 -> 2 [color=gray50, style=dashed];\n"));
+
+    let dot = to_string(|out| write_forest_dot(&graph, &forest, false, out).unwrap());
+    assert!(dot.contains("    1;\n"));
+}
+
+// The same text through the trait object for both representations.
+#[test]
+fn graph_trait() {
+    let lists = small();
+    let mut matrix = AdjMatrix::new(3);
+    matrix.add_edge(Edge::new(0, 1));
+    matrix.add_edge(Edge::new(1, 2));
+    matrix.add_edge(Edge::new(0, 2));
+
+    let graphs: [&dyn Graph; 2] = [&lists, &matrix];
+    let forest = dfs::seq(&lists);
+
+    let dots = graphs
+        .iter()
+        .map(|graph| to_string(|out| graph.write_dot(Some(&forest), true, out).unwrap()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(dots[0], dots[1]);
+}
+
+fn edges<'a, G: GraphRef<'a, Vertex = usize> + Copy>(graph: G) -> Vec<(usize, usize)> {
+    graph
+        .vertices()
+        .flat_map(|from| graph.neighbours(from).map(move |to| (from, to)))
+        .collect()
+}
+
+fn parse_text(format: Format, text: &str) -> io::Result<EdgeList> {
+    format.read(text.as_bytes())
+}
+
+fn error_message(result: io::Result<EdgeList>) -> String {
+    result.unwrap_err().to_string()
+}
+
+// Writing and reading back gives the same lists, in the same order.
+#[test]
+fn round_trip() {
+    let graph: AdjLists = AdjLists::gen_undirected(200, 1000, None);
+
+    for &format in &Format::ALL {
+        let mut out = Vec::new();
+        format.write(&graph, &mut out).unwrap();
+
+        let read = format.read(&out[..]).unwrap();
+        assert_eq!(read.n_verts, 200, "{}", format);
+
+        let lists = read.into_adj_lists().unwrap();
+        assert_eq!(edges(&lists), edges(&graph), "{}", format);
+    }
+}
+
+#[test]
+fn dimacs() {
+    let text = "c a comment\np sp 3 2\nc another one\na 1 2 5\n\na 3 1 7\n";
+    let read = parse_text(Format::Dimacs, text).unwrap();
+
+    assert_eq!(read.n_verts, 3);
+    assert_eq!(read.edges, vec![Edge::new(0, 1), Edge::new(2, 0)]);
+
+    let message = error_message(Format::Dimacs.read("p sp 3 1\na 1 4 1\n".as_bytes()));
+    assert!(message.contains("line 2"), "{}", message);
+
+    let message = error_message(Format::Dimacs.read("p sp 3 2\na 1 2 1\n".as_bytes()));
+    assert!(message.contains("expected 2 arcs"), "{}", message);
+}
+
+#[test]
+fn metis() {
+    // Edge weights, and vertex 3 has no neighbours
+    let text = "% comment\n4 2 001\n2 10 4 20\n1 10\n\n1 20\n";
+    let read = parse_text(Format::Metis, text).unwrap();
+
+    assert_eq!(read.n_verts, 4);
+    assert_eq!(
+        read.edges,
+        vec![Edge::new(0, 1), Edge::new(0, 3), Edge::new(1, 0), Edge::new(3, 0)]
+    );
+
+    // Vertex weights with two constraints
+    let text = "2 1 010 2\n5 6 2\n7 8 1\n";
+    assert_eq!(parse_text(Format::Metis, text).unwrap().edges.len(), 2);
+
+    let message = error_message(parse_text(Format::Metis, "3 1\n2\n1\n"));
+    assert!(message.contains("neighbours of vertex 3"), "{}", message);
+
+    let mut directed: AdjLists = AdjLists::new(2);
+    directed.add_edge(Edge::new(0, 1));
+    let error = Format::Metis.write(&directed, Vec::new()).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn matrix_market() {
+    let text = "%%MatrixMarket matrix coordinate real symmetric\n% comment\n3 3 2\n2 1 0.5\n3 2 1.5\n";
+    let read = parse_text(Format::MatrixMarket, text).unwrap();
+
+    assert_eq!(read.n_verts, 3);
+    assert_eq!(
+        read.edges,
+        vec![Edge::new(1, 0), Edge::new(0, 1), Edge::new(2, 1), Edge::new(1, 2)]
+    );
+
+    let text = "%%MatrixMarket matrix coordinate pattern general\n2 3 0\n";
+    assert!(error_message(parse_text(Format::MatrixMarket, text)).contains("not square"));
+
+    let text = "%%MatrixMarket matrix array real general\n2 2\n";
+    assert!(parse_text(Format::MatrixMarket, text).is_err());
+}
+
+#[test]
+fn snap() {
+    let text = "# Directed graph\n# Nodes: 5 Edges: 2\n0\t3\n3 1\n";
+    let read = parse_text(Format::Snap, text).unwrap();
+
+    assert_eq!(read.n_verts, 5);
+    assert_eq!(read.edges, vec![Edge::new(0, 3), Edge::new(3, 1)]);
+
+    assert_eq!(parse_text(Format::Snap, "7 2\n").unwrap().n_verts, 8);
+    assert!(error_message(parse_text(Format::Snap, "1 2 3\n")).contains("line 1"));
+}
+
+// Huge counts in a header are an error, not an allocation failure.
+#[test]
+fn huge_counts() {
+    let huge = [
+        (Format::Dimacs, "p sp 1 18446744073709551615\n"),
+        (Format::Dimacs, "p sp 1000000000000 0\n"),
+        (Format::Metis, "1 9223372036854775807\n\n"),
+        (Format::Metis, "1000000000000 0\n"),
+        (Format::MatrixMarket, "%%MatrixMarket matrix coordinate pattern general\n1 1 18446744073709551615\n"),
+        (Format::MatrixMarket, "%%MatrixMarket matrix coordinate pattern general\n1000000000000 1000000000000 0\n"),
+        (Format::Snap, "# Nodes: 1000000000000\n"),
+    ];
+
+    for &(format, text) in &huge {
+        let error = parse_text(format, text).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{:?}: {}", format, error);
+
+        let error = parse_par(text.as_bytes(), format, false).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{:?}: {}", format, error);
+    }
+
+    // Vertices used by edges are allowed, however many there are
+    let read = parse_text(Format::Snap, "# Nodes: 100001\n0 100000\n").unwrap();
+    assert_eq!(read.n_verts, 100_001);
+}
+
+#[test]
+fn validate() {
+    let mut list = parse_text(Format::Snap, "0 1\n1 1\n0 2\n0 1\n2 0\n").unwrap();
+
+    assert!(list.validate().is_err());
+    assert!(list.clone().into_adj_lists().is_err());
+    assert!(list.clone().into_adj_matrix().is_err());
+
+    assert_eq!(list.remove_invalid(), 2);
+    assert_eq!(list.edges, vec![Edge::new(0, 1), Edge::new(0, 2), Edge::new(2, 0)]);
+    assert!(list.validate().is_ok());
+
+    let matrix = list.clone().into_adj_matrix().unwrap();
+    assert_eq!(edges(&matrix), vec![(0, 1), (0, 2), (2, 0)]);
+}
+
+#[test]
+fn detect() {
+    let path = |name: &str| PathBuf::from(name);
+
+    assert_eq!(Format::from_extension(&path("road.gr")), Some(Format::Dimacs));
+    assert_eq!(Format::from_extension(&path("a/b.GRAPH")), Some(Format::Metis));
+    assert_eq!(Format::from_extension(&path("web.txt")), Some(Format::Snap));
+    assert_eq!(Format::from_extension(&path("graph")), None);
+
+    assert_eq!(
+        Format::detect(&path("x.txt"), "%%MatrixMarket matrix coordinate pattern general\n"),
+        Some(Format::MatrixMarket)
+    );
+    assert_eq!(Format::detect(&path("x"), "c USA road network\n"), Some(Format::Dimacs));
+    assert_eq!(Format::detect(&path("x.mtx"), "# comment\n"), Some(Format::Snap));
+    assert_eq!(Format::detect(&path("x.graph"), "10 20\n"), Some(Format::Metis));
+    assert_eq!(Format::detect(&path("x"), "10 20\n"), None);
+
+    for &format in &Format::ALL {
+        assert_eq!(format.name().parse::<Format>(), Ok(format));
+    }
+}
+
+#[test]
+fn files() {
+    let graph: AdjLists = AdjLists::gen_directed(50, 200, None);
+    let dir = env::temp_dir();
+
+    for &format in &[Format::Dimacs, Format::MatrixMarket, Format::Snap] {
+        let path = dir.join(format!("parallel-dfs-{}.{}", format, format.extensions()[0]));
+
+        write_file(&graph, &path).unwrap();
+        let read = read_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(edges(&read.into_adj_lists().unwrap()), edges(&graph), "{}", format);
+    }
+}
+
+/// Lists of `graph` with every list sorted.
+fn sorted_lists<'a, G: GraphRef<'a, Vertex = usize> + Copy>(graph: G) -> Vec<Vec<usize>> {
+    graph
+        .vertices()
+        .map(|v| {
+            let mut list = graph.neighbours(v).collect::<Vec<_>>();
+            list.sort_unstable();
+            list
+        })
+        .collect()
+}
+
+// Large enough for several chunks, so that line and vertex numbers have to
+// be carried between them.
+#[test]
+fn parse_par_same_as_read() {
+    let graph: AdjLists = AdjLists::gen_undirected(2_000, 10_000, None);
+
+    for &format in &Format::ALL {
+        let mut text = Vec::new();
+        format.write(&graph, &mut text).unwrap();
+
+        let (parsed, stats) = parse_par(&text, format, false).unwrap();
+        let read = format.read(&text[..]).unwrap().into_adj_lists().unwrap();
+
+        assert!(stats.chunks > 1, "{}", format);
+        assert_eq!(stats.bytes, text.len());
+        assert_eq!(stats.edges, 20_000);
+        assert_eq!(parsed.n_verts(), read.n_verts(), "{}", format);
+        assert_eq!(sorted_lists(&parsed), sorted_lists(&read), "{}", format);
+    }
+}
+
+#[test]
+fn parse_par_formats() {
+    let dimacs = "c comment\n\np sp 3 2\na 1 2 5\nc again\na 3 1 7";
+    let (graph, stats) = parse_par(dimacs.as_bytes(), Format::Dimacs, false).unwrap();
+    assert_eq!(edges(&graph), vec![(0, 1), (2, 0)]);
+    assert_eq!(stats.lines, 6);
+
+    let metis = "% comment\n4 2 001\n4 20 2 10\n1 10\n\n1 20\n\n";
+    let (graph, _) = parse_par(metis.as_bytes(), Format::Metis, false).unwrap();
+    assert_eq!(edges(&graph), vec![(0, 1), (0, 3), (1, 0), (3, 0)]);
+
+    let mtx = "%%MatrixMarket matrix coordinate pattern symmetric\n3 3 1\n3 1\n";
+    let (graph, _) = parse_par(mtx.as_bytes(), Format::MatrixMarket, false).unwrap();
+    assert_eq!(edges(&graph), vec![(0, 2), (2, 0)]);
+
+    let snap = "# Nodes: 5 Edges: 2\r\n3\t0\r\n0 1\r\n";
+    let (graph, _) = parse_par(snap.as_bytes(), Format::Snap, false).unwrap();
+    assert_eq!(graph.n_verts(), 5);
+    assert_eq!(edges(&graph), vec![(0, 1), (3, 0)]);
+
+    let (graph, stats) = parse_par(b"", Format::Snap, false).unwrap();
+    assert_eq!(graph.n_verts(), 0);
+    assert_eq!(stats.lines, 0);
+}
+
+#[test]
+fn parse_par_errors() {
+    let message = |text: &str, format| parse_par(text.as_bytes(), format, false).unwrap_err().to_string();
+
+    // The bad line is in the last chunk
+    let mut text = (0..2_000).map(|i| format!("{} {}\n", i, i + 1)).collect::<String>();
+    text.push_str("1 x\n");
+    assert!(message(&text, Format::Snap).starts_with("line 2001:"));
+
+    assert!(message("p sp 2 2\na 1 2 1\n", Format::Dimacs).contains("expected 2 arcs"));
+    assert!(message("c only comments\n", Format::Dimacs).contains("line 2: expected problem line"));
+    assert!(message("3 1\n2\n1\n", Format::Metis).contains("line 4: expected neighbours of vertex 3"));
+    assert!(message("2 0\n\n\n1\n", Format::Metis).contains("line 4: expected only 2 vertex lines"));
+    assert!(message("%%MatrixMarket matrix array real general\n", Format::MatrixMarket).contains("line 1"));
+}
+
+#[test]
+fn parse_par_invalid() {
+    let text = "0 1\n1 1\n0 2\n0 1\n2 0\n";
+
+    let message = parse_par(text.as_bytes(), Format::Snap, false).unwrap_err().to_string();
+    assert!(message.contains("loops"), "{}", message);
+
+    let message = parse_par(b"0 1\n0 1\n", Format::Snap, false).unwrap_err().to_string();
+    assert!(message.contains("multiple edges"), "{}", message);
+
+    let (graph, stats) = parse_par(text.as_bytes(), Format::Snap, true).unwrap();
+    assert_eq!(edges(&graph), vec![(0, 1), (0, 2), (2, 0)]);
+    assert_eq!(stats.edges, 3);
+    assert_eq!(stats.removed, 2);
+    assert!(stats.to_string().contains("removed 2 invalid edges"));
+}
+
+#[test]
+fn read_file_par_detects() {
+    let graph: AdjLists = AdjLists::gen_directed(50, 200, None);
+    let path = env::temp_dir().join("parallel-dfs-par.mtx");
+
+    write_file(&graph, &path).unwrap();
+    let (parsed, stats) = read_file_par(&path, false).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(stats.edges, 200);
+    assert_eq!(sorted_lists(&parsed), sorted_lists(&graph));
+
+    let path = env::temp_dir().join("parallel-dfs-empty.txt");
+    std::fs::write(&path, "").unwrap();
+    let (parsed, _) = read_file_par(&path, false).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(parsed.n_verts(), 0);
+}
+
+#[test]
+fn edge_writer() {
+    let mut writer = EdgeWriter::new(Format::Snap, vec![], 3, 2).unwrap();
+    writer.edge(0, 1).unwrap();
+    writer.edge(2, 1).unwrap();
+    let out = writer.finish().unwrap();
+
+    let graph = Format::Snap.read(&out[..]).unwrap().into_adj_lists().unwrap();
+    assert_eq!(graph.edges().collect::<Vec<_>>(), vec![Edge::new(0, 1), Edge::new(2, 1)]);
+
+    let mut writer = EdgeWriter::new(Format::Dimacs, vec![], 3, 2).unwrap();
+    writer.edge(0, 1).unwrap();
+    assert_eq!(writer.finish().unwrap_err().kind(), io::ErrorKind::InvalidInput);
+
+    let err = EdgeWriter::new(Format::Metis, vec![], 3, 2).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
@@ -1,10 +1,16 @@
 #![feature(no_more_cas)]
 
 extern crate crossbeam;
+extern crate crossbeam_deque;
+#[cfg(unix)]
+extern crate libc;
 extern crate rand;
 extern crate rayon;
 extern crate spin;
 
+pub mod bfs;
 pub mod dfs;
 pub mod graph;
+pub mod io;
+pub mod progress;
 mod utils;
@@ -1,35 +1,42 @@
 use parallel_dfs::dfs;
-use parallel_dfs::graph::{AdjLists, AdjMatrix};
+use parallel_dfs::graph::adj_lists::stream;
+use parallel_dfs::graph::{self, Graph, MappedGraph, Representation, Tree};
+use parallel_dfs::io::{is_binary_file, read_file_par, Format};
+use parallel_dfs::progress::{self, Phase, Progress};
 use rayon::ThreadPoolBuilder;
 use structopt::StructOpt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
 
-enum Algorithm {
-    GenList,
-    SeqList,
-    ParList,
-    CheatList,
-    GenMatrix,
-    SeqMatrix,
-    ParMatrix,
-    CheatMatrix,
+/// What to run, given on the command line as `<algorithm>_<representation>`,
+/// e.g. `par_mat`, or as `gen_<representation>` to only generate the graph.
+struct Choice {
+    algorithm: Option<dfs::Algorithm>,
+    representation: Representation,
 }
 
-impl FromStr for Algorithm {
+impl FromStr for Choice {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "gen_list" => Ok(Algorithm::GenList),
-            "seq_list" => Ok(Algorithm::SeqList),
-            "par_list" => Ok(Algorithm::ParList),
-            "cheat_list" => Ok(Algorithm::CheatList),
-            "gen_mat" => Ok(Algorithm::GenMatrix),
-            "seq_mat" => Ok(Algorithm::SeqMatrix),
-            "par_mat" => Ok(Algorithm::ParMatrix),
-            "cheat_mat" => Ok(Algorithm::CheatMatrix),
-            _ => Err(format!("unknown algorithm {:?}", s))
-        }
+        let split = s.rfind('_').ok_or_else(|| format!("unknown algorithm {:?}", s))?;
+
+        let algorithm = match &s[..split] {
+            "gen" => None,
+            algorithm => Some(algorithm.parse()?),
+        };
+
+        Ok(Choice {
+            algorithm,
+            representation: s[split + 1..].parse()?,
+        })
     }
 }
 
@@ -48,16 +55,216 @@ enum Opts {
         /// Number of threads to use. Defaults to number of logical CPUs.
         #[structopt(short = "t", long = "threads")]
         threads: Option<usize>,
-        /// Which algorithm to use.
+        /// Which algorithm to use, e.g. `par_mat`. See `algos` for all of them.
         #[structopt(long = "algo")]
-        algorithm: Option<Algorithm>,
+        algorithm: Option<Choice>,
         /// Generate undirected graph. Defaults to directed.
         #[structopt(long = "undirected")]
         undirected: bool,
         /// Whether to write the result to stdout.
         #[structopt(long = "output")]
         output: bool,
+        /// Show a progress line on stderr.
+        #[structopt(long = "progress")]
+        progress: bool,
+        /// Write the graph and the forest in the GraphViz DOT format to this file.
+        #[structopt(long = "dot", parse(from_os_str))]
+        dot: Option<PathBuf>,
+        /// Write the graph in the binary format to this file, for `run`.
+        #[structopt(long = "save", parse(from_os_str))]
+        save: Option<PathBuf>,
+        /// Seed for the random generator. The same seed gives the same adjacency lists.
+        #[structopt(long = "seed")]
+        seed: Option<u64>,
+        /// Write the adjacency lists to this file while generating them, without keeping
+        /// the graph in memory. The format is chosen by the extension, e.g. `.txt` for SNAP.
+        #[structopt(
+            long = "to-file",
+            parse(from_os_str),
+            raw(conflicts_with_all = r#"&["algorithm", "output", "dot", "save"]"#)
+        )]
+        to_file: Option<PathBuf>,
+    },
+    /// Traverse a graph read from a file
+    #[structopt(name = "run")]
+    Run {
+        /// Binary graph file saved with `gen --save`, which is memory-mapped,
+        /// or a DIMACS, METIS, Matrix Market or SNAP file, which is parsed in
+        /// parallel.
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+        /// Drop loops and multiple edges from text files instead of failing.
+        #[structopt(long = "remove-invalid")]
+        remove_invalid: bool,
+        /// Check the whole binary file, not only its header, before the
+        /// traversal.
+        #[structopt(long = "verify")]
+        verify: bool,
+        /// Number of threads to use. Defaults to number of logical CPUs.
+        #[structopt(short = "t", long = "threads")]
+        threads: Option<usize>,
+        /// Which algorithm to use, e.g. `par`. See `algos` for all of them.
+        #[structopt(long = "algo", default_value = "par")]
+        algorithm: dfs::Algorithm,
+        /// Whether to write the result to stdout.
+        #[structopt(long = "output")]
+        output: bool,
+        /// Show a progress line on stderr.
+        #[structopt(long = "progress")]
+        progress: bool,
+        /// Write the graph and the forest in the GraphViz DOT format to this file.
+        #[structopt(long = "dot", parse(from_os_str))]
+        dot: Option<PathBuf>,
     },
+    /// List the available algorithms and graph representations
+    #[structopt(name = "algos")]
+    Algos,
+}
+
+/// Progress sink which keeps totals for the status line.
+#[derive(Default)]
+struct StatusLine {
+    phase: Mutex<Option<Phase>>,
+    edges: AtomicUsize,
+    vertices: AtomicUsize,
+    done: AtomicBool,
+}
+
+impl Progress for StatusLine {
+    fn phase(&self, phase: Phase) {
+        *self.phase.lock().unwrap() = Some(phase);
+    }
+
+    fn edges_generated(&self, n: usize) {
+        self.edges.fetch_add(n, Ordering::SeqCst);
+    }
+
+    fn vertices_visited(&self, n: usize) {
+        self.vertices.fetch_add(n, Ordering::SeqCst);
+    }
+}
+
+impl StatusLine {
+    /// Redraw the status line every `interval` until `done` is set.
+    fn render(&self, interval: Duration) {
+        while !self.done.load(Ordering::SeqCst) {
+            thread::sleep(interval);
+
+            let phase = match *self.phase.lock().unwrap() {
+                Some(phase) => format!("{:?}", phase),
+                None => String::from("Starting"),
+            };
+
+            eprint!(
+                "\r{:<9}: {} edges generated, {} vertices visited",
+                phase,
+                self.edges.load(Ordering::SeqCst),
+                self.vertices.load(Ordering::SeqCst),
+            );
+        }
+
+        eprintln!();
+    }
+}
+
+/// Write `graph` with `forest` drawn over it to a DOT file.
+fn write_dot(graph: &dyn Graph, forest: Option<&[Tree]>, path: &Path) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    graph.write_dot(forest, true, &mut out)?;
+    out.flush()
+}
+
+/// Write `graph` to a file in the binary format.
+fn write_binary(graph: &dyn Graph, path: &Path) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    graph.write_binary(&mut out)?;
+    out.flush()
+}
+
+/// Generate a graph like `Representation::Lists` would, writing it straight
+/// to `path`.
+fn gen_to_file(
+    n_verts: usize,
+    n_edges: usize,
+    undirected: bool,
+    seed: Option<u64>,
+    path: &Path,
+) -> io::Result<()> {
+    let format = Format::from_extension(path).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "unknown extension, use e.g. .gr, .mtx or .txt")
+    })?;
+
+    // Checked before the file is created, so a failure doesn't leave it empty
+    if !format.is_streamable() {
+        let message = format!("can't generate {} files, use e.g. .gr, .mtx or .txt", format.name());
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+    }
+
+    let out = BufWriter::new(File::create(path)?);
+    let seeds = seed.into_iter().flat_map(graph::seeds);
+
+    match undirected {
+        true => stream::gen_undirected(n_verts, n_edges, seeds, format, out),
+        false => stream::gen_directed(n_verts, n_edges, seeds, format, out),
+    }
+}
+
+/// Read the graph of `run` from `path`, printing how long it took.
+fn load(path: &Path, remove_invalid: bool, verify: bool) -> io::Result<Box<dyn Graph>> {
+    let start = std::time::Instant::now();
+
+    match is_binary_file(path)? {
+        true => {
+            let graph: MappedGraph = MappedGraph::open(path)?;
+
+            if verify {
+                graph.verify()?;
+            }
+
+            println!("graph load: {:?}", std::time::Instant::now().duration_since(start));
+
+            Ok(Box::new(graph))
+        },
+        false => {
+            let (graph, stats) = read_file_par(path, remove_invalid)?;
+            println!("graph parse: {}", stats);
+
+            Ok(Box::new(graph))
+        },
+    }
+}
+
+/// Traverse `graph` with `algorithm`, if given, and write the results.
+fn traverse(
+    graph: &dyn Graph,
+    algorithm: Option<dfs::Algorithm>,
+    config: &dfs::DfsConfig,
+    output: bool,
+    dot: Option<PathBuf>,
+) {
+    let start = std::time::Instant::now();
+
+    let forest = match algorithm {
+        Some(algorithm) => {
+            let forest = algorithm.run_with(graph, config);
+            println!("total dfs: {:?}", std::time::Instant::now().duration_since(start));
+
+            forest
+        },
+        None => vec![],
+    };
+
+    if output {
+        println!("{:#?}", forest);
+    }
+
+    if let Some(path) = dot {
+        let forest = algorithm.map(|_| forest.as_slice());
+
+        if let Err(err) = write_dot(graph, forest, &path) {
+            eprintln!("can't write {}: {}", path.display(), err);
+        }
+    }
 }
 
 fn main() {
@@ -66,7 +273,7 @@ fn main() {
     // Manually build the global thread pool so we can set the number
     // of threads to use
     let thread_pool = match opts {
-        Opts::Gen { threads: Some(t), .. } => {
+        Opts::Gen { threads: Some(t), .. } | Opts::Run { threads: Some(t), .. } => {
             ThreadPoolBuilder::new().num_threads(t).build().unwrap()
         },
         _ => {
@@ -74,81 +281,81 @@ fn main() {
         }
     };
 
+    let status_line = match opts {
+        Opts::Gen { progress: true, .. } | Opts::Run { progress: true, .. } => {
+            let status_line: &'static StatusLine = Box::leak(Box::new(StatusLine::default()));
+            progress::set_sink(status_line);
+
+            Some((status_line, thread::spawn(move || status_line.render(Duration::from_millis(200)))))
+        },
+        _ => None,
+    };
+
+    // Runs on the pool installed below, only the timings are needed
+    let dfs_config = dfs::DfsConfig {
+        timings: true,
+        ..dfs::DfsConfig::default()
+    };
+
     thread_pool.install(|| {
         match opts {
-            Opts::Gen { undirected, vertices, edges, output, algorithm, .. } => {
-                let algorithm = algorithm.unwrap_or(Algorithm::ParMatrix);
-                let forest = match algorithm {
-                    Algorithm::GenList => {
-                        let start = std::time::Instant::now();
-                        let _graph = match undirected {
-                            true => AdjLists::gen_undirected(vertices, edges, None),
-                            false => AdjLists::gen_directed(vertices, edges, None),
-                        };
-
-                        let after_gen = std::time::Instant::now();
-                        println!("graph gen: {:?}", after_gen.duration_since(start));
-
-                        vec![]
-                    },
-                    Algorithm::GenMatrix => {
-                        let start = std::time::Instant::now();
-                        let _graph = match undirected {
-                            true => AdjMatrix::gen_undirected(vertices, edges, None),
-                            false => AdjMatrix::gen_directed(vertices, edges, None),
-                        };
+            Opts::Gen { undirected, vertices, edges, seed, to_file: Some(path), .. } => {
+                let start = std::time::Instant::now();
+
+                if let Err(err) = gen_to_file(vertices, edges, undirected, seed, &path) {
+                    eprintln!("can't write {}: {}", path.display(), err);
+                    process::exit(1);
+                }
+
+                println!("graph gen: {:?}", std::time::Instant::now().duration_since(start));
+            },
+            Opts::Gen { undirected, vertices, edges, output, algorithm, dot, save, seed, .. } => {
+                let choice = algorithm.unwrap_or(Choice {
+                    algorithm: Some(dfs::Algorithm::Par),
+                    representation: Representation::Matrix,
+                });
 
-                        let after_gen = std::time::Instant::now();
-                        println!("graph gen: {:?}", after_gen.duration_since(start));
+                let start = std::time::Instant::now();
+                let graph = choice.representation.generate(vertices, edges, undirected, seed);
 
-                        vec![]
+                println!("graph gen: {:?}", std::time::Instant::now().duration_since(start));
+
+                if let Some(path) = save {
+                    if let Err(err) = write_binary(&*graph, &path) {
+                        eprintln!("can't write {}: {}", path.display(), err);
                     }
-                    Algorithm::SeqList | Algorithm::ParList | Algorithm::CheatList => {
-                        let start = std::time::Instant::now();
-                        let graph = match undirected {
-                            true => AdjLists::gen_undirected(vertices, edges, None),
-                            false => AdjLists::gen_directed(vertices, edges, None),
-                        };
-
-                        let after_gen = std::time::Instant::now();
-                        println!("graph gen: {:?}", after_gen.duration_since(start));
-
-                        let forest = match algorithm {
-                            Algorithm::SeqList => dfs::seq(&graph),
-                            Algorithm::ParList => dfs::par(&graph),
-                            Algorithm::CheatList => dfs::cheat(&graph),
-                            _ => unreachable!()
-                        };
-                        println!("total dfs: {:?}", std::time::Instant::now().duration_since(after_gen));
-
-                        forest
-                    },
-                    Algorithm::SeqMatrix | Algorithm::ParMatrix | Algorithm::CheatMatrix => {
-                        let start = std::time::Instant::now();
-                        let graph = match undirected {
-                            true => AdjMatrix::gen_undirected(vertices, edges, None),
-                            false => AdjMatrix::gen_directed(vertices, edges, None),
-                        };
-
-                        let after_gen = std::time::Instant::now();
-                        println!("graph gen: {:?}", after_gen.duration_since(start));
-
-                        let forest = match algorithm {
-                            Algorithm::SeqMatrix => dfs::seq(&graph),
-                            Algorithm::ParMatrix => dfs::par(&graph),
-                            Algorithm::CheatMatrix => dfs::cheat(&graph),
-                            _ => unreachable!()
-                        };
-                        println!("total dfs: {:?}", std::time::Instant::now().duration_since(after_gen));
-
-                        forest
+                }
+
+                traverse(&*graph, choice.algorithm, &dfs_config, output, dot);
+            },
+            Opts::Run { file, remove_invalid, verify, algorithm, output, dot, .. } => {
+                let graph = match load(&file, remove_invalid, verify) {
+                    Ok(graph) => graph,
+                    Err(err) => {
+                        eprintln!("can't read {}: {}", file.display(), err);
+                        process::exit(1);
                     },
                 };
 
-                if output {
-                    println!("{:#?}", forest);
+                traverse(&*graph, Some(algorithm), &dfs_config, output, dot);
+            },
+            Opts::Algos => {
+                println!("algorithms:");
+                println!("    {:<8}{}", "gen", "only generate the graph");
+                for algorithm in &dfs::Algorithm::ALL {
+                    println!("    {:<8}{}", algorithm.name(), algorithm.description());
+                }
+
+                println!("representations:");
+                for representation in &Representation::ALL {
+                    println!("    {:<8}{}", representation.name(), representation.description());
                 }
             },
         }
     });
+
+    if let Some((status_line, handle)) = status_line {
+        status_line.done.store(true, Ordering::SeqCst);
+        handle.join().unwrap();
+    }
 }
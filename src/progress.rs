@@ -0,0 +1,147 @@
+//! Progress reporting
+//!
+//! Long running operations (graph generation and traversals) report what they
+//! are doing to a sink. Traversals use the sink from their `DfsConfig`, so
+//! runs at the same time can report to different sinks. Everything else, and
+//! traversals without a sink of their own, report to a global sink, similar
+//! to how the `log` crate works. By default there is no sink and reports are
+//! ignored.
+
+/// Number of vertices a worker visits before reporting them.
+const VISITS_PER_REPORT: usize = 1024;
+
+/// Number of edges a generator job adds before reporting them.
+pub(crate) const EDGES_PER_REPORT: usize = 1024;
+
+/// What is currently being done.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Generating the edges of a random graph.
+    Generate,
+    /// Adding the reverse edges of an undirected graph.
+    Mirror,
+    /// Parsing a graph file.
+    Parse,
+    /// Traversing the graph.
+    Traverse,
+    /// The descend phase of `dfs::par`.
+    Descend,
+    /// The backtrack phase of `dfs::par`.
+    Backtrack,
+    /// Turning a spanning forest into a DFS forest with `dfs::repair`.
+    Repair,
+}
+
+/// Receiver of progress reports.
+///
+/// Reports come from all worker threads, so implementations must be thread-safe.
+/// Counts are increments, not totals.
+pub trait Progress: Sync {
+    fn phase(&self, phase: Phase);
+    fn edges_generated(&self, n: usize);
+    fn vertices_visited(&self, n: usize);
+}
+
+/// The global sink.
+static SINK: spin::Once<&'static dyn Progress> = spin::Once::INIT;
+
+/// Set the global sink, which receives the reports of everything that
+/// doesn't have a sink of its own.
+///
+/// The sink can only be set once. Returns `false`, and leaves the sink as it
+/// is, if it was already set.
+pub fn set_sink(sink: &'static dyn Progress) -> bool {
+    let mut set = false;
+
+    SINK.call_once(|| {
+        set = true;
+        sink
+    });
+
+    set
+}
+
+fn global() -> Option<&'static dyn Progress> {
+    // `try` is a keyword since the 2018 edition
+    SINK.r#try().cloned()
+}
+
+pub(crate) fn phase(phase: Phase) {
+    Sink::global().phase(phase);
+}
+
+pub(crate) fn edges_generated(n: usize) {
+    Sink::global().edges_generated(n);
+}
+
+pub(crate) fn vertices_visited(n: usize) {
+    Sink::global().vertices_visited(n);
+}
+
+/// Where the reports of an operation go.
+#[derive(Clone, Copy)]
+pub(crate) struct Sink<'p>(Option<&'p dyn Progress>);
+
+impl<'p> Sink<'p> {
+    pub fn global() -> Sink<'static> {
+        Sink(global())
+    }
+
+    /// `sink`, or the global sink if there isn't one.
+    pub fn or_global(sink: Option<&'p dyn Progress>) -> Sink<'p> {
+        match sink {
+            Some(sink) => Sink(Some(sink)),
+            None => Sink(global()),
+        }
+    }
+
+    pub fn phase(self, phase: Phase) {
+        if let Some(sink) = self.0 {
+            sink.phase(phase);
+        }
+    }
+
+    pub fn edges_generated(self, n: usize) {
+        if let Some(sink) = self.0 {
+            sink.edges_generated(n);
+        }
+    }
+
+    pub fn vertices_visited(self, n: usize) {
+        if let Some(sink) = self.0 {
+            sink.vertices_visited(n);
+        }
+    }
+}
+
+/// Per worker counter of visited vertices.
+///
+/// Reports in batches, so that workers don't hit the sink for every vertex.
+/// Whatever is left is reported when the counter is dropped.
+pub(crate) struct Visits<'p> {
+    sink: Sink<'p>,
+    count: usize,
+}
+
+impl<'p> Visits<'p> {
+    pub fn new(sink: Sink<'p>) -> Self {
+        Visits { sink, count: 0 }
+    }
+
+    pub fn add(&mut self) {
+        self.count += 1;
+
+        if self.count == VISITS_PER_REPORT {
+            self.sink.vertices_visited(self.count);
+            self.count = 0;
+        }
+    }
+}
+
+impl<'p> Drop for Visits<'p> {
+    fn drop(&mut self) {
+        if self.count > 0 {
+            self.sink.vertices_visited(self.count);
+        }
+    }
+}
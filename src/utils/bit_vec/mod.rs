@@ -87,6 +87,11 @@ impl BitVec {
     pub fn iter(&self) -> Iter {
         self.as_full_slice().into_iter()
     }
+
+    /// Returns the number of set bits in this vector
+    pub fn count_ones(&self) -> usize {
+        self.as_full_slice().count_ones()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -165,6 +170,32 @@ impl<'a> BitSlice<'a> {
         self.len() == 0
     }
 
+    /// Returns the number of set bits in this slice
+    pub fn count_ones(&self) -> usize {
+        let end = self.start_offset + self.nbits;
+
+        self.storage
+            .iter()
+            .enumerate()
+            .map(|(i, block)| {
+                let block_start = i * B_BITS;
+                let mut bits = block.load(Ordering::SeqCst);
+
+                // Only the first block can have bits before the start
+                if block_start < self.start_offset {
+                    bits &= !((1 << self.start_offset) - 1);
+                }
+
+                // and only the last one bits after the end
+                if end < block_start + B_BITS {
+                    bits &= (1 << (end - block_start)) - 1;
+                }
+
+                bits.count_ones() as usize
+            })
+            .sum()
+    }
+
     pub fn slice<R>(&self, index: R) -> BitSlice<'a>
     where
         R: RangeBounds<usize> + Debug,
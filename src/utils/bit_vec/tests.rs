@@ -155,3 +155,20 @@ fn ones_rev_front_and_back() {
     assert_eq!(iter.next(), Some(0));
     assert_eq!(iter.next(), None);
 }
+
+#[test]
+fn count_ones() {
+    let a = BitVec::new(200);
+
+    for &i in &[0, 1, 63, 64, 65, 127, 128, 199] {
+        a.set(i, true);
+    }
+
+    assert_eq!(a.count_ones(), 8);
+    assert_eq!(a.slice(..).count_ones(), 8);
+    assert_eq!(a.slice(1..).count_ones(), 7);
+    assert_eq!(a.slice(2..64).count_ones(), 1);
+    assert_eq!(a.slice(63..66).count_ones(), 3);
+    assert_eq!(a.slice(64..64).count_ones(), 0);
+    assert_eq!(a.slice(66..199).count_ones(), 2);
+}
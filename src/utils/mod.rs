@@ -1,3 +1,5 @@
 pub mod bit_vec;
+pub mod sharded_set;
 
 pub use self::bit_vec::BitVec;
+pub use self::sharded_set::ShardedSet;
@@ -0,0 +1,56 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashSet;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::sync::Mutex;
+
+/// Number of shards for each thread in the pool. More shards mean less
+/// contention, but more memory for small sets.
+const SHARDS_PER_THREAD: usize = 4;
+
+/// A hash set which supports concurrent insertion.
+///
+/// The elements are split between several independently locked sets by their
+/// hash, so threads block each other only when they hit the same shard.
+#[derive(Debug)]
+pub struct ShardedSet<T> {
+    shards: Box<[Mutex<HashSet<T>>]>,
+    hasher: RandomState,
+}
+
+impl<T: Hash + Eq> ShardedSet<T> {
+    /// Create new empty set with enough shards for the current thread pool.
+    pub fn new() -> Self {
+        Self::with_shards((rayon::current_num_threads() * SHARDS_PER_THREAD).next_power_of_two())
+    }
+
+    /// Create new empty set with `n_shards` shards.
+    pub fn with_shards(n_shards: usize) -> Self {
+        assert!(n_shards > 0);
+
+        ShardedSet {
+            shards: (0..n_shards).map(|_| Mutex::new(HashSet::new())).collect(),
+            hasher: RandomState::new(),
+        }
+    }
+
+    /// Add `value` to the set. Returns `true` if it wasn't already there.
+    ///
+    /// When many threads insert the same value, exactly one of them gets `true`.
+    pub fn insert(&self, value: T) -> bool {
+        self.shard(&value).lock().unwrap().insert(value)
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.shard(value).lock().unwrap().contains(value)
+    }
+
+    fn shard(&self, value: &T) -> &Mutex<HashSet<T>> {
+        let mut hasher = self.hasher.build_hasher();
+        value.hash(&mut hasher);
+
+        &self.shards[hasher.finish() as usize % self.shards.len()]
+    }
+}
+
+#[cfg(test)]
+mod tests;
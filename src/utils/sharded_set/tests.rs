@@ -0,0 +1,28 @@
+use rayon::prelude::*;
+use super::*;
+
+#[test]
+fn insert() {
+    let set = ShardedSet::with_shards(4);
+
+    assert!(set.insert(1));
+    assert!(set.insert(2));
+    assert!(!set.insert(1));
+
+    assert!(set.contains(&1));
+    assert!(!set.contains(&3));
+}
+
+#[test]
+fn insert_concurrent() {
+    let set = ShardedSet::new();
+
+    // Every value is inserted 4 times, but only one of them may succeed
+    let inserted = (0..40_000)
+        .into_par_iter()
+        .filter(|&i| set.insert(i % 10_000))
+        .count();
+
+    assert_eq!(inserted, 10_000);
+    assert!((0..10_000).all(|i| set.contains(&i)));
+}